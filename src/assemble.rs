@@ -0,0 +1,114 @@
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::error::Error;
+use crate::links;
+use crate::outline;
+use crate::page_meta::{self, PageKind, PageMeta};
+use crate::page_tree;
+
+/// 조립할 페이지 출처
+enum Part {
+    /// 페이지를 가진 문서 (SVG 페이지, back matter 등)
+    Doc(Box<Document>),
+    /// 생성된 빈 페이지 (소속 섹션)
+    Blank(String),
+}
+
+/// 책 조립기: 페이지 출처를 순서대로 모아 두었다가 `finish`에서 한 번에 병합.
+/// 객체 번호는 출처마다 한 번만 배정하고, 페이지 트리는 마지막에 한 번 균형 있게 구성하므로
+/// 시간·메모리가 페이지 수에 선형이다
+pub struct Assembly {
+    /// 첫 문서(front matter). 카탈로그(책갈피, 이름 트리 등)가 결과 문서의 것이 됨
+    base: Document,
+    /// 페이지 1장짜리 빈 페이지 템플릿. 빈 페이지들은 콘텐츠/리소스를 공유
+    blank_template: Document,
+    parts: Vec<Part>,
+    page_count: usize,
+}
+
+impl Assembly {
+    pub fn new(base: Document, blank_template: Document) -> Assembly {
+        let page_count = base.get_pages().len();
+        Assembly { base, blank_template, parts: Vec::new(), page_count }
+    }
+
+    /// 지금까지 쌓인 페이지 수 (다음 페이지는 `page_count() + 1`쪽)
+    pub fn page_count(&self) -> usize {
+        self.page_count
+    }
+
+    pub fn push(&mut self, doc: Document) {
+        self.page_count += doc.get_pages().len();
+        self.parts.push(Part::Doc(Box::new(doc)));
+    }
+
+    /// `section` 소속으로 태깅된 빈 페이지 1장
+    pub fn push_blank(&mut self, section: &str) {
+        self.page_count += 1;
+        self.parts.push(Part::Blank(section.to_string()));
+    }
+
+    /// 모든 출처를 `base`로 옮기고 페이지 트리를 새로 구성
+    pub fn finish(self) -> Result<Document, Box<dyn Error>> {
+        let Assembly { mut base, blank_template, parts, page_count } = self;
+        let root = page_tree::root(&base)?;
+        let mut pages = Vec::with_capacity(page_count);
+        pages.extend(page_tree::take_pages(&mut base)?);
+
+        let mut template = Some(blank_template);
+        let mut blank_proto: Option<Dictionary> = None;
+        let mut outline_items = Vec::new();
+
+        for part in parts {
+            match part {
+                Part::Doc(mut add) => {
+                    add.renumber_objects_with(base.max_id + 1);
+                    // 이후 base에 새로 만드는 객체가 add 번호와 겹치지 않도록
+                    base.max_id = base.max_id.max(add.max_id);
+                    links::merge_named_dests(&mut base, &mut add)?;
+                    // add의 카탈로그는 버려지므로 책갈피 최상위 항목은 base 책갈피 끝에 이어 붙임
+                    outline_items.extend(outline::top_level_items(&add));
+                    pages.extend(move_pages(&mut base, *add)?);
+                }
+                Part::Blank(section) => {
+                    if blank_proto.is_none()
+                        && let Some(mut t) = template.take()
+                    {
+                        t.renumber_objects_with(base.max_id + 1);
+                        base.max_id = base.max_id.max(t.max_id);
+                        let first = page_tree::take_pages(&mut t)?.first().copied().ok_or("blank template has no pages")?;
+                        blank_proto = Some(t.get_object(first)?.as_dict()?.clone());
+                        t.objects.remove(&first);
+                        move_objects(&mut base, t);
+                    }
+                    let proto = blank_proto.clone().ok_or("blank template has no pages")?;
+                    let pid = base.add_object(proto);
+                    let mut meta = PageMeta::new(PageKind::Blank);
+                    meta.section = section;
+                    page_meta::set(&mut base, pid, &meta)?;
+                    pages.push(pid);
+                }
+            }
+        }
+
+        if !outline_items.is_empty() {
+            outline::append_top_level(&mut base, &outline_items)?;
+        }
+        page_tree::build_balanced(&mut base, root, &pages)?;
+        base.renumber_objects();
+        Ok(base)
+    }
+}
+
+/// `add`의 페이지를 트리에서 떼어 나머지 객체와 함께 `base`로 옮김 (카탈로그 제외)
+fn move_pages(base: &mut Document, mut add: Document) -> Result<Vec<ObjectId>, Box<dyn Error>> {
+    let pages = page_tree::take_pages(&mut add)?;
+    move_objects(base, add);
+    Ok(pages)
+}
+
+fn move_objects(base: &mut Document, mut add: Document) {
+    if let Ok(catalog) = add.trailer.get(b"Root").and_then(Object::as_reference) {
+        add.objects.remove(&catalog);
+    }
+    base.objects.extend(add.objects);
+}
@@ -17,21 +17,32 @@ impl UnitSystem {
     }
 }
 
+/// Reading / binding direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum BindingDirection {
+    /// Left-to-right: bound on the left, odd pages (recto) on the right
+    #[default]
+    Ltr,
+    /// Right-to-left: bound on the right, odd pages (recto) on the left
+    Rtl,
+}
+
 #[derive(Debug)]
 pub struct BookParams {
     pub width: f64,
     pub height: f64,
-    pub unit_system: UnitSystem,
     pub pages: i64,
+    pub direction: BindingDirection,
 }
 
 impl BookParams {
-    pub fn new(width: f64, height: f64, unit_system: UnitSystem, pages: i64) -> Self {
+    pub fn new(width: f64, height: f64, pages: i64, direction: BindingDirection) -> Self {
         Self {
             width,
             height,
-            unit_system,
             pages,
+            direction,
         }
     }
 }
@@ -40,6 +51,9 @@ impl BookParams {
 pub struct BookBindingConstant {
     /// per-edge bleed (usually 0.125")
     pub bleed_cover: f64,
+    /// interior bleed past the top, bottom and outer edges (usually 0.125");
+    /// the binder replaces it with the full-bleed pages' `bleed` (0 when no page bleeds)
+    pub bleed_interior: f64,
     /// cover margin (applied equally to top/bottom/left/right)
     pub margin_cover: f64,
     /// thickness per page
@@ -70,6 +84,14 @@ pub struct Rect {
     pub height: f64,
 }
 
+/// Cover wrap panels, in cover coordinates (origin bottom-left, bleed included)
+#[derive(Debug, Clone, Copy)]
+pub struct CoverLayout {
+    pub back: Rect,
+    pub spine: Rect,
+    pub front: Rect,
+}
+
 impl Book {
     pub fn new(params: BookParams, binding: BookBindingConstant) -> Self {
         Self { params, binding }
@@ -100,7 +122,46 @@ impl Book {
         Size { width: w, height: h }
     }
 
-    /// Get safe area rect (if is_left: true = left page (verso), false = right page (recto))
+    /// Whether the 1-based page number lands on the left-hand side of a spread
+    /// (LTR: even pages = verso; RTL: odd pages = recto)
+    pub fn is_left_page(&self, page_number: usize) -> bool {
+        let is_recto = page_number % 2 == 1;
+        match self.params.direction {
+            BindingDirection::Ltr => !is_recto,
+            BindingDirection::Rtl => is_recto,
+        }
+    }
+
+    /// Get cover panels (back, spine, front) from left to right, including bleed.
+    /// RTL books put the front cover on the left.
+    pub fn get_cover_layout(&self) -> CoverLayout {
+        let cover = self.get_cover_size();
+        let spine = self.get_spine_width();
+        let panel_w = (cover.width - spine) / 2.0;
+        let left = Rect { x: 0.0, y: 0.0, width: panel_w, height: cover.height };
+        let spine_rect = Rect { x: panel_w, y: 0.0, width: spine, height: cover.height };
+        let right = Rect { x: panel_w + spine, y: 0.0, width: panel_w, height: cover.height };
+        match self.params.direction {
+            BindingDirection::Ltr => CoverLayout { back: left, spine: spine_rect, front: right },
+            BindingDirection::Rtl => CoverLayout { back: right, spine: spine_rect, front: left },
+        }
+    }
+
+    /// Get interior trim rect (the finished page, origin at the page origin)
+    pub fn get_trim_box(&self) -> Rect {
+        Rect { x: 0.0, y: 0.0, width: self.params.width, height: self.params.height }
+    }
+
+    /// Get interior bleed rect: the trim extended past the top, bottom and outer edges
+    /// (never past the spine edge). Equal to the trim box when there is no bleed
+    pub fn get_bleed_box(&self, is_left: bool) -> Rect {
+        let b = self.binding.bleed_interior;
+        let x = if is_left { -b } else { 0.0 };
+        Rect { x, y: -b, width: self.params.width + b, height: self.params.height + 2.0 * b }
+    }
+
+    /// Get safe area rect (if is_left: true = left-hand page, false = right-hand page;
+    /// the gutter is always on the edge facing the spine)
     pub fn get_safe_area(&self, is_left: bool) -> Rect {
         let safe = self.get_safe_area_size();
         let x = if is_left { self.binding.margin_inner } else { self.binding.gutter };
@@ -115,12 +176,32 @@ impl Book {
     }
 }
 
+/// Interior paper stock (sets page thickness, and so the spine width)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Paper {
+    /// KDP black & white on white paper
+    #[default]
+    White,
+    /// KDP black & white on cream paper
+    Cream,
+}
+
+impl Paper {
+    pub fn binding(self) -> BookBindingConstant {
+        match self {
+            Paper::White => BINDING_PARAMS_KDP_WHITE,
+            Paper::Cream => BINDING_PARAMS_KDP_CREAM,
+        }
+    }
+}
+
 // const THICKNESS_PREMIUM: f64 = 0.002347;
 const THICKNESS_WHITE: f64 = 0.002252;
 const THICKNESS_CREAM: f64 = 0.0025;
 
 pub const BINDING_PARAMS_KDP_WHITE: BookBindingConstant = BookBindingConstant {
     bleed_cover: 0.125,         // KDP default
+    bleed_interior: 0.125,      // KDP default (no bleed on the spine edge)
     margin_cover: 0.125,        // conservative cover margin when bleed is present
     thickness: THICKNESS_WHITE, // example: 120p B/W White (0.002252 * 120 ≈ 0.270; varies by vendor)
     gutter: 0.375,              // inner margin
@@ -129,6 +210,7 @@ pub const BINDING_PARAMS_KDP_WHITE: BookBindingConstant = BookBindingConstant {
 
 pub const BINDING_PARAMS_KDP_CREAM: BookBindingConstant = BookBindingConstant {
     bleed_cover: 0.125,         // KDP default
+    bleed_interior: 0.125,      // KDP default (no bleed on the spine edge)
     margin_cover: 0.125,        // conservative cover margin when bleed is present
     thickness: THICKNESS_CREAM, // example: 120p B/W Cream (0.0025 * 120 ≈ 0.300; varies by vendor)
     gutter: 0.375,              // inner margin
@@ -0,0 +1,504 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use crate::binding_params::BindingDirection;
+use crate::process_pages::{Anchor, FitMode, FitPolicies, FitPolicy};
+
+/// 프로젝트 설정 (`materials/book.toml`). 모든 항목은 생략 가능
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// 책 제목 (머리말 `{title}`)
+    pub title: Option<String>,
+    /// 제본 방향 ("ltr" / "rtl"). `--direction` 플래그가 우선
+    pub direction: Option<BindingDirection>,
+    /// 전역 svg2pdf 변환 옵션
+    pub svg: SvgOptions,
+    /// 모든 SVG 페이지의 기본값. manifest 항목과 sidecar가 덮어씀
+    pub page_defaults: PageConfig,
+    /// 페이지별 항목 (SVG 파일명 키, 예: "003.svg")
+    pub pages: HashMap<String, PageConfig>,
+    /// 섹션 이름(`front`, `body`, `back` 또는 페이지의 `section = "..."`로 붙인 이름,
+    /// 예: `appendix`)별 설정
+    pub sections: HashMap<String, SectionConfig>,
+    /// 빈 뒷면 페이지 정책
+    pub blanks: BlankConfig,
+    /// 쪽번호. 이 테이블이 있을 때만 찍음
+    pub folio: Option<FolioConfig>,
+    /// 머리말/꼬리말. 이 테이블이 있을 때만 찍음
+    pub running: Option<RunningConfig>,
+    /// 책갈피 (문서 outline)
+    pub outline: OutlineConfig,
+    /// `apply_inner_margin` 피팅 정책: `[fit.default]`와 이를 덮어쓰는 섹션별 테이블
+    /// (`[fit.front]`, `[fit.body]`, `[fit.back]`)
+    pub fit: HashMap<String, FitPolicyConfig>,
+    /// 판형과 크기가 다른 front/back matter 페이지 처리
+    pub matter: MatterConfig,
+}
+
+/// 섹션 첫 페이지가 놓일 면
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SectionStart {
+    /// 홀수 쪽
+    Recto,
+    /// 짝수 쪽
+    Verso,
+    #[default]
+    Any,
+}
+
+/// 섹션별 설정
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SectionConfig {
+    /// 이 규칙을 맞추려고 섹션 앞에 빈 페이지를 넣음 (기본: any).
+    /// front는 항상 1쪽(recto)에서 시작하므로 `verso`는 설정 오류
+    pub start: SectionStart,
+    /// 표시 제목 (기본: 섹션 이름)
+    pub title: Option<String>,
+    /// 이 섹션의 머리말/꼬리말 템플릿 (`[running]`을 덮어씀)
+    pub running: RunningTemplates,
+    /// 이 섹션 쪽번호 접두사 (쪽번호와 PDF 페이지 라벨), 예: "A-"
+    pub label_prefix: Option<String>,
+    /// 섹션 첫 페이지에서 아라비아 숫자 쪽번호를 이 값(1 이상)으로 다시 시작
+    pub first_number: Option<i64>,
+}
+
+/// 머리말/꼬리말 템플릿. 치환자: `{title}`, `{section}`, `{page}`, `{total}`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunningTemplates {
+    pub header_verso: Option<String>,
+    pub header_recto: Option<String>,
+    pub footer_verso: Option<String>,
+    pub footer_recto: Option<String>,
+}
+
+impl RunningTemplates {
+    /// `over`의 설정값이 있으면 우선 (빈 문자열이면 해당 위치 끔)
+    pub fn merged(&self, over: &RunningTemplates) -> RunningTemplates {
+        RunningTemplates {
+            header_verso: over.header_verso.clone().or_else(|| self.header_verso.clone()),
+            header_recto: over.header_recto.clone().or_else(|| self.header_recto.clone()),
+            footer_verso: over.footer_verso.clone().or_else(|| self.footer_verso.clone()),
+            footer_recto: over.footer_recto.clone().or_else(|| self.footer_recto.clone()),
+        }
+    }
+}
+
+/// `[running]` 테이블
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunningConfig {
+    /// 표준 14 폰트 이름 (기본: Helvetica)
+    pub font: String,
+    /// 글자 크기 pt (기본: 9)
+    pub size: f64,
+    /// 머리말/꼬리말 글줄과 그림 사이 간격 pt, 0 이상 (기본: 0).
+    /// 세이프 영역 위(머리말)/아래(꼬리말, 쪽번호 위)에 글자 크기 + offset 높이의 띠를 떼어 글을 놓고,
+    /// 그림은 남은 영역에 맞춤. 템플릿이 모두 비어 있는 쪽의 띠는 잡지 않음
+    pub offset: f64,
+    /// front matter에는 찍지 않음 (기본: true)
+    pub skip_front: bool,
+    /// 기본: `{title}`
+    pub header_verso: Option<String>,
+    /// 기본: `{section}`
+    pub header_recto: Option<String>,
+    pub footer_verso: Option<String>,
+    pub footer_recto: Option<String>,
+}
+
+impl RunningConfig {
+    /// 전역 템플릿 ← `[sections.<name>.running]`
+    pub fn templates(&self, section: Option<&SectionConfig>) -> RunningTemplates {
+        let base = RunningTemplates {
+            header_verso: self.header_verso.clone(),
+            header_recto: self.header_recto.clone(),
+            footer_verso: self.footer_verso.clone(),
+            footer_recto: self.footer_recto.clone(),
+        };
+        match section {
+            Some(s) => base.merged(&s.running),
+            None => base,
+        }
+    }
+}
+
+impl Default for RunningConfig {
+    fn default() -> Self {
+        RunningConfig {
+            font: String::from("Helvetica"),
+            size: 9.0,
+            offset: 0.0,
+            skip_front: true,
+            header_verso: Some(String::from("{title}")),
+            header_recto: Some(String::from("{section}")),
+            footer_verso: None,
+            footer_recto: None,
+        }
+    }
+}
+
+/// SVG 페이지 주변 빈 페이지 삽입 위치. `[sections]` 시작 면 규칙과 spread에 필요한 빈 페이지는
+/// 정책과 관계없이 들어감. (변형 설명은 `--blank-policy` 도움말에 그대로 나오므로 영어)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum BlankPolicy {
+    /// A blank behind every illustration (one side printed)
+    SingleSided,
+    /// No backing blanks
+    None,
+    /// Every illustration starts on a recto; a blank is added only when one would land on a verso
+    BeforeRecto,
+    /// One blank at each section boundary, none between illustrations
+    BetweenSections,
+}
+
+/// 생성된 빈 페이지에 그릴 내용
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BlankTemplate {
+    /// 없음
+    #[default]
+    Empty,
+    /// 가운데 한 줄 텍스트 (`notice_text`)
+    Notice,
+    /// 비침/마커 시험용 사각형 격자
+    Swatches,
+    /// 사용자 SVG 또는 PDF의 첫 페이지 (`template_file`)
+    File,
+}
+
+/// `[blanks]` 테이블
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlankConfig {
+    /// 기본: single-sided, ARC는 none. `--blank-policy` 플래그가 우선
+    pub policy: Option<BlankPolicy>,
+    /// single-sided에서 마지막 일러스트 뒤 빈 페이지 유지 (기본: true)
+    pub trailing: Option<bool>,
+    /// 전체 쪽수가 홀수면 끝에 빈 페이지 추가 (기본: true, ARC는 false)
+    pub even_page_count: Option<bool>,
+    /// 생성된 빈 페이지 내용 (기본: empty)
+    pub template: BlankTemplate,
+    /// `notice` 템플릿 문구 (기본: "This page intentionally left blank.")
+    pub notice_text: Option<String>,
+    /// `file` 템플릿용 SVG 또는 PDF
+    pub template_file: Option<PathBuf>,
+    /// 빈 페이지 제거 시 이미지를 디코딩해서, 전부 흰색이거나 완전 투명한 이미지는
+    /// 페이지를 남기지 않게 함 (기본: false)
+    pub inspect_images: bool,
+    /// 이미지 픽셀이 흰색에서 이만큼(성분당 8비트 단계) 떨어져도 빈 것으로 봄
+    /// (기본: 4)
+    pub image_tolerance: Option<u8>,
+}
+
+impl BlankConfig {
+    /// 이미지 검사 허용 오차 (0.0..=1.0), 검사를 끄면 None
+    pub fn image_tolerance(&self) -> Option<f64> {
+        self.inspect_images.then(|| self.image_tolerance.unwrap_or(4) as f64 / 255.0)
+    }
+}
+
+/// 판형과 크기가 다른 front/back matter 페이지 배치 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatterResize {
+    /// 페이지 박스만 판형으로 바꿈. 콘텐츠 위치는 그대로라 잘림
+    #[default]
+    Crop,
+    /// 페이지 전체가 판형 안에 들어가도록 확대/축소 후 가운데 정렬
+    Fit,
+    /// 판형을 덮도록 확대/축소 후 가운데 정렬, 넘치는 부분은 잘림
+    Fill,
+    /// 원래 배율 그대로 판형 가운데에 놓음
+    Center,
+}
+
+/// `[matter]` 테이블
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatterConfig {
+    /// 크기가 다른 페이지 배치 (기본: crop). 해당 페이지는 항상 경고로 보고
+    pub resize: MatterResize,
+}
+
+/// 세이프 영역 기준 쪽번호 위치
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FolioPosition {
+    /// 아래 바깥쪽 모서리 (짝수 쪽 왼쪽, 홀수 쪽 오른쪽)
+    #[default]
+    OuterCorner,
+    BottomCenter,
+}
+
+/// `[folio]` 테이블
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FolioConfig {
+    pub position: FolioPosition,
+    /// 표준 14 폰트 이름 (기본: Helvetica)
+    pub font: String,
+    /// 글자 크기 pt (기본: 10)
+    pub size: f64,
+    /// 쪽번호 글줄과 그림 사이 간격 pt, 0 이상 (기본: 0).
+    /// 세이프 영역 아래쪽에 글자 크기 + offset 높이의 띠를 떼어 쪽번호를 놓고, 그림은 그 위에 맞춤
+    pub offset: f64,
+    /// front matter 다음 첫 페이지 번호, 1 이상 (기본: 1)
+    pub start: i64,
+    /// front 섹션에는 쪽번호를 찍지 않음 (기본: true)
+    pub skip_front: bool,
+    /// 생성된 빈 페이지에는 쪽번호를 찍지 않음 (기본: true)
+    pub skip_blank: bool,
+    /// 풀블리드 페이지에는 쪽번호를 찍지 않음 (기본: true)
+    pub skip_full_bleed: bool,
+    /// front 섹션(그 안의 빈 페이지 포함)은 i, ii, iii…로 매기고 본문에서 `start`부터 다시 시작 (기본: true).
+    /// false면 첫 페이지부터 `start`로 시작하는 아라비아 숫자 하나로
+    pub front_roman: bool,
+}
+
+impl Default for FolioConfig {
+    fn default() -> Self {
+        FolioConfig {
+            position: FolioPosition::default(),
+            font: String::from("Helvetica"),
+            size: 10.0,
+            offset: 0.0,
+            start: 1,
+            skip_front: true,
+            skip_blank: true,
+            skip_full_bleed: true,
+            front_roman: true,
+        }
+    }
+}
+
+/// `[outline]` 테이블
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutlineConfig {
+    /// 섹션 구간마다 항목 하나씩 책갈피 작성 (기본: true)
+    pub enabled: bool,
+    /// 일러스트마다 섹션 아래 항목 추가. 제목은 SVG `<title>` 또는 파일명
+    /// (기본: false)
+    pub pages: bool,
+}
+
+impl Default for OutlineConfig {
+    fn default() -> Self {
+        OutlineConfig { enabled: true, pages: false }
+    }
+}
+
+/// 피팅 정책 덮어쓰기. 없는 값은 `[fit.default]`, 그다음 내장 정책을 따름
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FitPolicyConfig {
+    /// 잉크 면적 / 세이프 면적이 이 값 미만이면 희소 페이지 (내장: 0.12)
+    pub sparse_threshold: Option<f64>,
+    /// 희소 페이지 피벗 (내장: center-bottom)
+    pub sparse_anchor: Option<Anchor>,
+    /// 일반 페이지 피벗 (내장: center-center)
+    pub dense_anchor: Option<Anchor>,
+    /// 희소 페이지 최대 배율 (내장: 1.0, 확대 안 함)
+    pub sparse_max_scale: Option<f64>,
+    /// 일반 페이지 최대 배율 (내장: inf)
+    pub dense_max_scale: Option<f64>,
+    /// contain 또는 cover (내장: contain)
+    pub mode: Option<FitMode>,
+}
+
+impl FitPolicyConfig {
+    pub fn apply(&self, base: &FitPolicy) -> FitPolicy {
+        FitPolicy {
+            sparse_threshold: self.sparse_threshold.unwrap_or(base.sparse_threshold),
+            sparse_anchor: self.sparse_anchor.unwrap_or(base.sparse_anchor),
+            dense_anchor: self.dense_anchor.unwrap_or(base.dense_anchor),
+            sparse_max_scale: self.sparse_max_scale.unwrap_or(base.sparse_max_scale),
+            dense_max_scale: self.dense_max_scale.unwrap_or(base.dense_max_scale),
+            mode: self.mode.unwrap_or(base.mode),
+        }
+    }
+}
+
+/// 페이지별 덮어쓰기 (manifest 항목 및/또는 sidecar `003.svg.toml`).
+/// 없는 값은 기본 동작
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PageConfig {
+    /// 이 페이지만의 svg2pdf 옵션 (없는 값은 `[svg]`)
+    pub svg: SvgOptions,
+    /// SVG를 페이지에 (그리고 `apply_inner_margin`에서 세이프 영역에) 맞추는 방식
+    pub fit: Option<FitMode>,
+    /// 피팅 피벗, 예: `{ x = "center", y = "start" }` (y start = 아래쪽)
+    pub anchor: Option<Anchor>,
+    /// SVG 캔버스 대신 실제 그려진 영역(선 두께 포함) 기준으로 배치
+    pub fit_to_ink: Option<bool>,
+    /// 풀블리드 페이지: 재단선 밖으로 이만큼(책 단위) 더 채움.
+    /// 풀블리드 페이지는 `apply_inner_margin`을 건너뛰고 쪽번호 없음.
+    /// 책 전체가 같은 블리드를 쓰므로(MediaBox 통일) 모든 풀블리드 페이지에서 같은 값이어야 함
+    pub bleed: Option<f64>,
+    /// 이 페이지 뒤에 빈 페이지 삽입 (기본: `[blanks] policy`에 따름)
+    pub blank_after: Option<bool>,
+    /// `apply_inner_margin`에서 세이프 영역으로 스케일 (기본: true)
+    pub inner_margin: Option<bool>,
+    /// 이 페이지에 쪽번호 허용 (기본: true, 제외 규칙은 `[folio]`)
+    pub folio: Option<bool>,
+    /// 소속 섹션 (기본: "body"). 같은 섹션이 이어지는 페이지들이 한 구간;
+    /// `[sections]` 참고
+    pub section: Option<String>,
+}
+
+impl PageConfig {
+    /// `over`의 설정값이 있으면 우선
+    pub fn merged(&self, over: &PageConfig) -> PageConfig {
+        PageConfig {
+            svg: self.svg.merged(&over.svg),
+            fit: over.fit.or(self.fit),
+            anchor: over.anchor.or(self.anchor),
+            fit_to_ink: over.fit_to_ink.or(self.fit_to_ink),
+            bleed: over.bleed.or(self.bleed),
+            blank_after: over.blank_after.or(self.blank_after),
+            inner_margin: over.inner_margin.or(self.inner_margin),
+            folio: over.folio.or(self.folio),
+            section: over.section.clone().or_else(|| self.section.clone()),
+        }
+    }
+}
+
+/// svg2pdf 변환 옵션. `None`이면 기본값
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SvgOptions {
+    /// 글꼴 임베드 대신 텍스트를 패스로 변환
+    pub text_to_paths: Option<bool>,
+    /// 필터 등 래스터화되는 효과의 해상도 배율
+    pub raster_scale: Option<f32>,
+    /// 콘텐츠 스트림 압축
+    pub compress: Option<bool>,
+    /// PDF/A-2b에서 금지된 구성 회피
+    pub pdfa: Option<bool>,
+}
+
+impl SvgOptions {
+    /// `over`의 설정값이 있으면 우선, 없으면 self 값 유지
+    pub fn merged(&self, over: &SvgOptions) -> SvgOptions {
+        SvgOptions {
+            text_to_paths: over.text_to_paths.or(self.text_to_paths),
+            raster_scale: over.raster_scale.or(self.raster_scale),
+            compress: over.compress.or(self.compress),
+            pdfa: over.pdfa.or(self.pdfa),
+        }
+    }
+
+    pub fn to_conversion_options(&self) -> svg2pdf::ConversionOptions {
+        let mut opts = svg2pdf::ConversionOptions::default();
+        if let Some(v) = self.text_to_paths { opts.embed_text = !v; }
+        if let Some(v) = self.raster_scale  { opts.raster_scale = v; }
+        if let Some(v) = self.compress      { opts.compress = v; }
+        if let Some(v) = self.pdfa          { opts.pdfa = v; }
+        opts
+    }
+}
+
+impl ProjectConfig {
+    /// 파일이 없으면 기본 설정
+    pub fn load(path: &Path) -> Result<ProjectConfig, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(ProjectConfig::default());
+        }
+        let text = std::fs::read_to_string(path)?;
+        let cfg: ProjectConfig = toml::from_str(&text)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        cfg.validate().map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(cfg)
+    }
+
+    /// 파싱만으로는 못 거르는 값 검사.
+    /// 쪽번호는 PDF 페이지 라벨 `/St`(1 이상)로도 기록되므로 1보다 작은 시작 번호는 거부
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(folio) = &self.folio {
+            if folio.start < 1 {
+                return Err(format!("[folio] start must be at least 1 (got {})", folio.start).into());
+            }
+            // 음수 offset은 글줄을 그림 쪽으로 밀어 넣으므로 거부 (띠 높이 = 글자 크기 + offset)
+            if folio.size <= 0.0 || folio.offset < 0.0 {
+                return Err("[folio] size must be positive and offset must not be negative".into());
+            }
+        }
+        if let Some(running) = &self.running
+            && (running.size <= 0.0 || running.offset < 0.0)
+        {
+            return Err("[running] size must be positive and offset must not be negative".into());
+        }
+        // 책은 front matter 첫 페이지(1쪽, 홀수)로 시작하므로 front 앞에는 빈 페이지를 넣을 자리가 없다
+        if self.section_start("front") == SectionStart::Verso {
+            return Err("[sections.front] start = \"verso\" is impossible: the book opens with the front section on page 1 (recto)".into());
+        }
+        for (name, section) in &self.sections {
+            if let Some(n) = section.first_number.filter(|n| *n < 1) {
+                return Err(format!("[sections.{name}] first_number must be at least 1 (got {n})").into());
+            }
+        }
+        Ok(())
+    }
+
+    /// 페이지 설정: `[page_defaults]` ← manifest 항목(`[pages."x.svg"]`) ← sidecar(`x.svg.toml`) 순으로 덮어씀
+    pub fn page_config(&self, svg_path: &Path) -> Result<PageConfig, Box<dyn Error>> {
+        let name = svg_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let manifest = match self.pages.get(name) {
+            Some(p) => self.page_defaults.merged(p),
+            None => self.page_defaults.clone(),
+        };
+
+        let sidecar_path = sidecar_path(svg_path);
+        if !sidecar_path.exists() {
+            return Ok(manifest);
+        }
+        let text = std::fs::read_to_string(&sidecar_path)?;
+        let sidecar: PageConfig = toml::from_str(&text)
+            .map_err(|e| format!("{}: {e}", sidecar_path.display()))?;
+        Ok(manifest.merged(&sidecar))
+    }
+
+    /// 내장 정책 ← `[fit.default]` ← `[fit.<section>]` ← `cli` 순으로 덮어쓴 섹션별 정책.
+    /// 명시적인 CLI 플래그가 설정 파일보다 항상 우선
+    pub fn fit_policies(&self, cli: &FitPolicyConfig) -> FitPolicies {
+        let mut base = FitPolicy::default();
+        if let Some(d) = self.fit.get("default") {
+            base = d.apply(&base);
+        }
+
+        let sections = self.fit.iter()
+            .filter(|(name, _)| name.as_str() != "default")
+            .map(|(name, c)| (name.clone(), cli.apply(&c.apply(&base))))
+            .collect();
+        FitPolicies { default: cli.apply(&base), sections }
+    }
+
+    /// 섹션 표시 제목 (설정 없으면 섹션 이름)
+    pub fn section_title<'a>(&'a self, section: &'a str) -> &'a str {
+        self.sections.get(section).and_then(|s| s.title.as_deref()).unwrap_or(section)
+    }
+
+    /// 섹션 시작 면 규칙 (설정 없으면 any)
+    pub fn section_start(&self, section: &str) -> SectionStart {
+        self.sections.get(section).map(|s| s.start).unwrap_or_default()
+    }
+
+    /// 전역 `[svg]` + 페이지별 svg 옵션 병합 결과
+    pub fn svg_options(&self, page: &PageConfig) -> SvgOptions {
+        self.svg.merged(&page.svg)
+    }
+}
+
+/// `page.svg` → `page.svg.toml`
+fn sidecar_path(svg_path: &Path) -> PathBuf {
+    let mut name = svg_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".toml");
+    svg_path.with_file_name(name)
+}
@@ -0,0 +1,78 @@
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Object};
+use crate::binding_params::{Book, Rect};
+use crate::process_pages::helvetica_text_width;
+
+/// 표지 템플릿 이름표 글자 크기 pt
+const LABEL_SIZE: f64 = 14.0;
+
+/// 인치 사각형 → pt `re` 연산
+fn rect_op(r: Rect) -> Operation {
+    Operation::new("re", vec![
+        (r.x * 72.0).into(), (r.y * 72.0).into(),
+        (r.width * 72.0).into(), (r.height * 72.0).into(),
+    ])
+}
+
+/// 표지 템플릿 크기 (pt)
+pub fn template_size(book: &Book) -> (f64, f64) {
+    let cover = book.get_cover_size();
+    (cover.width * 72.0, cover.height * 72.0)
+}
+
+/// 표지 템플릿 리소스 (이름표용 Helvetica)
+pub fn template_resources() -> Dictionary {
+    dictionary! {
+        "Font" => dictionary! {
+            "F1" => dictionary! {
+                "Type" => "Font",
+                "Subtype" => "Type1",
+                "BaseFont" => "Helvetica",
+                "Encoding" => "WinAnsiEncoding",
+            },
+        },
+    }
+}
+
+/// 표지 작업용 안내선: 뒤표지/책등/앞표지 패널 경계(실선), 재단선(점선), 패널 이름표.
+/// 패널 위치는 `Book::get_cover_layout`을 따르므로 우철(RTL)이면 앞표지가 왼쪽에 온다
+pub fn template_content(book: &Book) -> Vec<u8> {
+    let layout = book.get_cover_layout();
+    let (w, h) = template_size(book);
+    let bleed = book.binding.bleed_cover * 72.0;
+
+    let mut ops = vec![
+        Operation::new("q", vec![]),
+        Operation::new("G", vec![0.5.into()]),
+        Operation::new("w", vec![0.5.into()]),
+        rect_op(layout.back),
+        rect_op(layout.spine),
+        rect_op(layout.front),
+        Operation::new("S", vec![]),
+        // 재단선: 바깥 가장자리에서 표지 블리드만큼 안쪽
+        Operation::new("d", vec![Object::Array(vec![4.into(), 2.into()]), 0.into()]),
+        Operation::new("re", vec![bleed.into(), bleed.into(), (w - 2.0 * bleed).into(), (h - 2.0 * bleed).into()]),
+        Operation::new("S", vec![]),
+        Operation::new("g", vec![0.5.into()]),
+    ];
+
+    // 앞/뒤 표지 이름표는 패널 가운데, 책등 이름표는 90° 돌려 책등 가운데
+    for (label, panel, rotated) in [("BACK", layout.back, false), ("FRONT", layout.front, false), ("SPINE", layout.spine, true)] {
+        let tw = helvetica_text_width(label, LABEL_SIZE);
+        let (cx, cy) = ((panel.x + panel.width / 2.0) * 72.0, (panel.y + panel.height / 2.0) * 72.0);
+        let tm: [f64; 6] = if rotated {
+            [0.0, 1.0, -1.0, 0.0, cx + LABEL_SIZE * 0.35, cy - tw / 2.0]
+        } else {
+            [1.0, 0.0, 0.0, 1.0, cx - tw / 2.0, cy - LABEL_SIZE * 0.35]
+        };
+        ops.extend([
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec![Object::Name(b"F1".to_vec()), LABEL_SIZE.into()]),
+            Operation::new("Tm", tm.iter().map(|v| (*v).into()).collect()),
+            Operation::new("Tj", vec![Object::string_literal(label)]),
+            Operation::new("ET", vec![]),
+        ]);
+    }
+    ops.push(Operation::new("Q", vec![]));
+    Content { operations: ops }.encode().unwrap_or_default()
+}
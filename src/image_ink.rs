@@ -0,0 +1,533 @@
+use lopdf::{Dictionary, Document, Object, Stream};
+use std::io::Cursor;
+use zune_jpeg::JpegDecoder;
+use zune_jpeg::zune_core::colorspace::ColorSpace;
+use zune_jpeg::zune_core::options::DecoderOptions;
+
+/// 디코딩할 최대 픽셀 수 (이보다 크면 검사하지 않고 잉크로 취급).
+/// 샘플은 원시 바이트로만 들고 있으므로 CMYK 8비트 기준 최대 64MB
+const MAX_PIXELS: usize = 16 * 1024 * 1024;
+
+/// 이미지 픽셀의 색공간 (지원하는 것만)
+#[derive(Clone, Copy, PartialEq)]
+enum ImageSpace {
+    Gray,
+    Rgb,
+    Cmyk,
+}
+
+impl ImageSpace {
+    fn components(self) -> usize {
+        match self {
+            ImageSpace::Gray => 1,
+            ImageSpace::Rgb => 3,
+            ImageSpace::Cmyk => 4,
+        }
+    }
+}
+
+/// 디코딩된 샘플. 해제된 바이트를 그대로 들고, 성분값은 읽을 때 계산
+struct Samples {
+    width: usize,
+    height: usize,
+    space: ImageSpace,
+    bpc: usize,
+    /// 한 줄의 바이트 수
+    stride: usize,
+    data: Vec<u8>,
+    /// 성분별 /Decode 범위 (없으면 0..1)
+    decode: Vec<(f64, f64)>,
+}
+
+impl Samples {
+    /// 줄 안의 `i`번째 성분 원시값
+    fn raw(&self, row: &[u8], i: usize) -> u32 {
+        match self.bpc {
+            8 => row[i] as u32,
+            16 => u16::from_be_bytes([row[2 * i], row[2 * i + 1]]) as u32,
+            bpc => {
+                let bit = i * bpc;
+                ((row[bit / 8] >> (8 - bpc - bit % 8)) as u32) & ((1 << bpc) - 1)
+            }
+        }
+    }
+
+    /// 줄 안의 `i`번째 성분값 0.0..=1.0 (/Decode 적용)
+    fn value(&self, row: &[u8], i: usize) -> f64 {
+        let max = ((1u32 << self.bpc) - 1) as f64;
+        let (d0, d1) = self.decode.get(i % self.space.components()).copied().unwrap_or((0.0, 1.0));
+        d0 + self.raw(row, i) as f64 / max * (d1 - d0)
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.data.chunks_exact(self.stride).take(self.height)
+    }
+
+    /// 성분값, 픽셀 순서대로
+    fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        let n = self.space.components();
+        self.rows().flat_map(move |row| (0..self.width * n).map(move |i| self.value(row, i)))
+    }
+
+    /// 픽셀별 "종이색이 아님" 여부 (필요한 만큼만 계산)
+    fn inked(&self, tolerance: f64) -> impl Iterator<Item = bool> + '_ {
+        let n = self.space.components();
+        self.rows().flat_map(move |row| {
+            (0..self.width).map(move |x| {
+                (x * n..(x + 1) * n).any(|i| {
+                    let c = self.value(row, i);
+                    match self.space {
+                        ImageSpace::Gray | ImageSpace::Rgb => c < 1.0 - tolerance,
+                        ImageSpace::Cmyk => c > tolerance,
+                    }
+                })
+            })
+        })
+    }
+}
+
+/// 인라인 이미지(BI)는 약어 키를 쓰므로 두 이름을 모두 확인
+fn get<'a>(dict: &'a Dictionary, key: &[u8], abbr: &[u8]) -> Option<&'a Object> {
+    dict.get(key).or_else(|_| dict.get(abbr)).ok()
+}
+
+fn deref<'a>(doc: &'a Document, obj: &'a Object) -> &'a Object {
+    match obj {
+        Object::Reference(id) => doc.get_object(*id).unwrap_or(obj),
+        _ => obj,
+    }
+}
+
+fn number(obj: &Object) -> Option<f64> {
+    match obj {
+        Object::Integer(i) => Some(*i as f64),
+        Object::Real(r) => Some(*r as f64),
+        _ => None,
+    }
+}
+
+/// 이미지 색공간 → 지원 색공간 (Indexed / Separation / Lab 등은 None)
+fn image_space(doc: &Document, cs: &Object, resources: &Option<Dictionary>) -> Option<ImageSpace> {
+    match deref(doc, cs) {
+        Object::Name(n) => match n.as_slice() {
+            b"DeviceGray" | b"G" | b"CalGray" => Some(ImageSpace::Gray),
+            b"DeviceRGB" | b"RGB" | b"CalRGB" => Some(ImageSpace::Rgb),
+            b"DeviceCMYK" | b"CMYK" => Some(ImageSpace::Cmyk),
+            // 인라인 이미지는 리소스의 /ColorSpace 이름을 쓸 수 있음
+            name => {
+                let named = resources.as_ref()?.get(b"ColorSpace").ok()?;
+                let named = deref(doc, named).as_dict().ok()?.get(name).ok()?;
+                match deref(doc, named) {
+                    Object::Name(n) if n.as_slice() == name => None,
+                    other => image_space(doc, other, &None),
+                }
+            }
+        },
+        Object::Array(a) => match a.first().and_then(|f| f.as_name().ok())? {
+            b"CalGray" => Some(ImageSpace::Gray),
+            b"CalRGB" => Some(ImageSpace::Rgb),
+            b"ICCBased" => {
+                let icc = deref(doc, a.get(1)?).as_stream().ok()?;
+                match icc.dict.get(b"N").ok().and_then(number)? as i64 {
+                    1 => Some(ImageSpace::Gray),
+                    3 => Some(ImageSpace::Rgb),
+                    4 => Some(ImageSpace::Cmyk),
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 필터 이름 목록 (인라인 약어는 정식 이름으로)
+fn filters(doc: &Document, dict: &Dictionary) -> Vec<Vec<u8>> {
+    let names: Vec<Vec<u8>> = match get(dict, b"Filter", b"F").map(|f| deref(doc, f)) {
+        Some(Object::Name(n)) => vec![n.clone()],
+        Some(Object::Array(a)) => a.iter().filter_map(|f| f.as_name().ok().map(|n| n.to_vec())).collect(),
+        _ => Vec::new(),
+    };
+    names.into_iter()
+        .map(|n| match n.as_slice() {
+            b"Fl" => b"FlateDecode".to_vec(),
+            b"LZW" => b"LZWDecode".to_vec(),
+            b"A85" => b"ASCII85Decode".to_vec(),
+            b"DCT" => b"DCTDecode".to_vec(),
+            _ => n,
+        })
+        .collect()
+}
+
+/// 성분별 /Decode 범위 (없으면 빈 목록 = 모두 0..1)
+fn decode_ranges(doc: &Document, dict: &Dictionary) -> Vec<(f64, f64)> {
+    match get(dict, b"Decode", b"D").map(|d| deref(doc, d)) {
+        Some(Object::Array(a)) => a.chunks_exact(2)
+            .map(|p| (number(&p[0]).unwrap_or(0.0), number(&p[1]).unwrap_or(1.0)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 스트림을 이미지 샘플로 디코딩. 지원하지 않는 형식이면 None
+fn decode(doc: &Document, image: &Stream, resources: &Option<Dictionary>) -> Option<Samples> {
+    let dict = &image.dict;
+    let width = get(dict, b"Width", b"W").and_then(number)? as usize;
+    let height = get(dict, b"Height", b"H").and_then(number)? as usize;
+    if width == 0 || height == 0 || width.saturating_mul(height) > MAX_PIXELS {
+        return None;
+    }
+
+    let filters = filters(doc, dict);
+    let (last, rest) = match filters.split_last() {
+        Some((last, rest)) => (Some(last.as_slice()), rest),
+        None => (None, &filters[..]),
+    };
+    let decode = decode_ranges(doc, dict);
+
+    // DCT 이전 단계(드묾)는 지원하지 않음
+    if last == Some(b"DCTDecode".as_slice()) {
+        if !rest.is_empty() {
+            return None;
+        }
+        return decode_jpeg(&image.content, width, height, decode);
+    }
+
+    let space = match get(dict, b"ColorSpace", b"CS") {
+        Some(cs) => image_space(doc, cs, resources)?,
+        None => return None,
+    };
+    let bpc = get(dict, b"BitsPerComponent", b"BPC").and_then(number)? as usize;
+    if !matches!(bpc, 1 | 2 | 4 | 8 | 16) {
+        return None;
+    }
+
+    let data = if filters.is_empty() {
+        image.content.clone()
+    } else {
+        // lopdf가 약어를 모르므로 정식 이름으로 바꾼 사본으로 해제
+        let mut plain = image.clone();
+        plain.dict.set("Filter", Object::Array(filters.iter().map(|f| Object::Name(f.clone())).collect()));
+        if let Some(dp) = get(dict, b"DecodeParms", b"DP") {
+            plain.dict.set("DecodeParms", deref(doc, dp).clone());
+        }
+        plain.decompressed_content().ok()?
+    };
+
+    let stride = (width * space.components() * bpc).div_ceil(8);
+    if data.len() < stride * height {
+        return None;
+    }
+    Some(Samples { width, height, space, bpc, stride, data, decode })
+}
+
+/// JPEG에 Adobe APP14 표식이 있는지 (SOS 이전 마커만 훑음)
+fn has_adobe_marker(data: &[u8]) -> bool {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+    let mut i = 2;
+    while i + 4 <= data.len() && data[i] == 0xFF {
+        match data[i + 1] {
+            0xFF => i += 1, // 채움 바이트
+            0xDA | 0xD9 => break,
+            marker => {
+                if marker == 0xEE && data.get(i + 4..i + 9) == Some(b"Adobe".as_slice()) {
+                    return true;
+                }
+                i += 2 + u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+            }
+        }
+    }
+    false
+}
+
+/// JFIF YCbCr → RGB (0..=255)
+fn ycc_to_rgb(y: u8, cb: u8, cr: u8) -> [u8; 3] {
+    let (y, cb, cr) = (y as f64, cb as f64 - 128.0, cr as f64 - 128.0);
+    [
+        y + 1.402 * cr,
+        y - 0.344136 * cb - 0.714136 * cr,
+        y + 1.772 * cb,
+    ]
+    .map(|v| v.round().clamp(0.0, 255.0) as u8)
+}
+
+/// DCT(JPEG) 이미지 → 그레이 / RGB / CMYK 샘플.
+/// CMYK(YCCK) JPEG은 색 변환 없이 성분값 그대로 받고, Adobe 표식이 있으면
+/// Photoshop 관례대로 반전 저장된 것으로 보고(255 = 잉크 없음) /Decode보다 먼저 뒤집는다
+fn decode_jpeg(data: &[u8], width: usize, height: usize, decode: Vec<(f64, f64)>) -> Option<Samples> {
+    let mut decoder = JpegDecoder::new(Cursor::new(data));
+    decoder.decode_headers().ok()?;
+    let input = decoder.input_colorspace()?;
+    let (space, out) = match decoder.info()?.components {
+        1 => (ImageSpace::Gray, ColorSpace::Luma),
+        3 => (ImageSpace::Rgb, ColorSpace::RGB),
+        4 if matches!(input, ColorSpace::CMYK | ColorSpace::YCCK) => (ImageSpace::Cmyk, input),
+        _ => return None,
+    };
+    let mut decoder = JpegDecoder::new_with_options(
+        Cursor::new(data),
+        DecoderOptions::default().jpeg_set_out_colorspace(out),
+    );
+    let mut pixels = decoder.decode().ok()?;
+    let info = decoder.info()?;
+    let (w, h) = (info.width as usize, info.height as usize);
+    let n = space.components();
+    if (w, h) != (width, height) || pixels.len() < w * h * n {
+        return None;
+    }
+
+    // YCCK → (반전 저장된) CMYK: CMY = 255 - RGB, K는 그대로
+    if input == ColorSpace::YCCK {
+        for px in pixels.chunks_exact_mut(4) {
+            let rgb = ycc_to_rgb(px[0], px[1], px[2]);
+            for (c, v) in px.iter_mut().zip(rgb) {
+                *c = 255 - v;
+            }
+        }
+    }
+
+    // 반전 후 /Decode 적용 = 각 범위의 양 끝을 맞바꿈
+    let decode = if space == ImageSpace::Cmyk && has_adobe_marker(data) {
+        (0..n).map(|i| decode.get(i).copied().unwrap_or((0.0, 1.0)))
+            .map(|(d0, d1)| (d1, d0))
+            .collect()
+    } else {
+        decode
+    };
+    Some(Samples { width, height, space, bpc: 8, stride: w * n, data: pixels, decode })
+}
+
+/// 이미지가 보이는 흔적을 남기지 않는지 검사: 모든 픽셀이 흰색(허용 오차 이내)이거나
+/// /SMask가 모든 픽셀을 완전 투명하게 만들면 true.
+/// 픽셀은 한 번에 하나씩 읽어 잉크를 찾는 즉시 멈춘다.
+/// 디코딩할 수 없는 이미지(지원하지 않는 필터·색공간, 너무 큼)는 항상 false
+pub fn image_is_blank(doc: &Document, image: &Stream, resources: &Option<Dictionary>, tolerance: f64) -> bool {
+    let Some(samples) = decode(doc, image, resources) else { return false };
+
+    let alpha = image.dict.get(b"SMask").ok()
+        .and_then(|s| deref(doc, s).as_stream().ok())
+        .and_then(|s| decode(doc, s, &None))
+        .filter(|a| a.space == ImageSpace::Gray);
+    match alpha {
+        // 알파 채널과 크기가 같으면 픽셀 단위로 결합
+        Some(alpha) if (alpha.width, alpha.height) == (samples.width, samples.height) => {
+            !samples.inked(tolerance).zip(alpha.values()).any(|(ink, a)| ink && a > tolerance)
+        }
+        Some(alpha) => {
+            !samples.inked(tolerance).any(|ink| ink) || alpha.values().all(|a| a <= tolerance)
+        }
+        None => !samples.inked(tolerance).any(|ink| ink),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    /// 허프만 표 (길이별 개수, 값) → 값별 (코드, 길이)
+    fn canonical(bits: &[u8; 16], values: &[u8]) -> Vec<(u16, u8)> {
+        let mut codes = vec![(0, 0); 256];
+        let (mut code, mut k) = (0u16, 0);
+        for (len, &count) in bits.iter().enumerate() {
+            for _ in 0..count {
+                codes[values[k] as usize] = (code, len as u8 + 1);
+                code += 1;
+                k += 1;
+            }
+            code <<= 1;
+        }
+        codes
+    }
+
+    fn segment(marker: u8, data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0xFF, marker];
+        out.extend(((data.len() + 2) as u16).to_be_bytes());
+        out.extend(data);
+        out
+    }
+
+    /// 성분마다 단색인 16 × 16 기준선 JPEG (DC 계수만, 양자화 1).
+    /// `adobe`가 있으면 APP14 Adobe 표식과 변환 코드 (0 = CMYK, 2 = YCCK)
+    fn solid_jpeg(comps: &[u8], adobe: Option<u8>) -> Vec<u8> {
+        const DC_BITS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+        const AC_BITS: [u8; 16] = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let dc_values: Vec<u8> = (0..12).collect();
+        let (dc, ac) = (canonical(&DC_BITS, &dc_values), canonical(&AC_BITS, &[0]));
+        let n = comps.len() as u8;
+
+        let mut out = vec![0xFF, 0xD8];
+        if let Some(transform) = adobe {
+            out.extend(segment(0xEE, &[b"Adobe".as_slice(), &[0, 100, 0, 0, 0, 0, transform]].concat()));
+        }
+        out.extend(segment(0xDB, &[[0u8].as_slice(), &[1; 64]].concat()));
+        let mut sof = vec![8, 0, 16, 0, 16, n];
+        for i in 0..n {
+            sof.extend([i + 1, 0x11, 0]);
+        }
+        out.extend(segment(0xC0, &sof));
+        out.extend(segment(0xC4, &[[0x00].as_slice(), &DC_BITS, &dc_values].concat()));
+        out.extend(segment(0xC4, &[[0x10].as_slice(), &AC_BITS, &[0]].concat()));
+        let mut sos = vec![n];
+        for i in 0..n {
+            sos.extend([i + 1, 0]);
+        }
+        sos.extend([0, 63, 0]);
+        out.extend(segment(0xDA, &sos));
+
+        let mut bits = Vec::new();
+        let mut put = |code: u16, len: u8| bits.extend((0..len).rev().map(|i| (code >> i) & 1 == 1));
+        let mut pred = vec![0i32; comps.len()];
+        for _ in 0..4 {
+            for (c, &v) in comps.iter().enumerate() {
+                let dc_value = 8 * (v as i32 - 128);
+                let diff = dc_value - pred[c];
+                pred[c] = dc_value;
+                let cat = 32 - diff.unsigned_abs().leading_zeros();
+                let (code, len) = dc[cat as usize];
+                put(code, len);
+                if cat > 0 {
+                    let extra = if diff > 0 { diff } else { diff + (1 << cat) - 1 };
+                    put(extra as u16, cat as u8);
+                }
+                let (code, len) = ac[0];
+                put(code, len);
+            }
+        }
+        while bits.len() % 8 != 0 {
+            bits.push(true);
+        }
+        for byte in bits.chunks(8) {
+            let b = byte.iter().fold(0u8, |acc, &bit| (acc << 1) | bit as u8);
+            out.push(b);
+            if b == 0xFF {
+                out.push(0);
+            }
+        }
+        out.extend([0xFF, 0xD9]);
+        out
+    }
+
+    fn jpeg_image(comps: &[u8], adobe: Option<u8>, decode: Option<Vec<Object>>) -> Stream {
+        let mut dict = dictionary! {
+            "Type" => "XObject", "Subtype" => "Image",
+            "Width" => 16, "Height" => 16, "BitsPerComponent" => 8,
+            "ColorSpace" => if comps.len() == 4 { "DeviceCMYK" } else { "DeviceGray" },
+            "Filter" => "DCTDecode",
+        };
+        if let Some(d) = decode {
+            dict.set("Decode", d);
+        }
+        Stream::new(dict, solid_jpeg(comps, adobe))
+    }
+
+    fn gray_image(samples: Vec<u8>, width: i64) -> Stream {
+        let height = samples.len() as i64 / width;
+        Stream::new(
+            dictionary! {
+                "Type" => "XObject", "Subtype" => "Image",
+                "Width" => width, "Height" => height,
+                "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8,
+            },
+            samples,
+        )
+    }
+
+    fn blank(doc: &Document, image: &Stream) -> bool {
+        image_is_blank(doc, image, &None, 0.02)
+    }
+
+    #[test]
+    fn white_gray_samples_are_blank() {
+        let doc = Document::with_version("1.7");
+        assert!(blank(&doc, &gray_image(vec![255; 4], 2)));
+        assert!(!blank(&doc, &gray_image(vec![255, 255, 0, 255], 2)));
+    }
+
+    #[test]
+    fn decode_array_inverts_samples() {
+        let doc = Document::with_version("1.7");
+        let mut image = gray_image(vec![0; 4], 2);
+        assert!(!blank(&doc, &image));
+        image.dict.set("Decode", vec![1.into(), 0.into()]);
+        assert!(blank(&doc, &image));
+    }
+
+    #[test]
+    fn low_bit_depths_are_unpacked() {
+        let doc = Document::with_version("1.7");
+        // 1비트 3 × 2, 줄마다 바이트 경계로 채움: 모두 흰색 → 둘째 줄 가운데만 검정
+        let mut image = gray_image(vec![0b1110_0000, 0b1110_0000], 1);
+        image.dict.set("Width", 3);
+        image.dict.set("BitsPerComponent", 1);
+        assert!(blank(&doc, &image));
+        image.content = vec![0b1110_0000, 0b1010_0000];
+        assert!(!blank(&doc, &image));
+    }
+
+    #[test]
+    fn gray_jpeg() {
+        let doc = Document::with_version("1.7");
+        assert!(blank(&doc, &jpeg_image(&[255], None, None)));
+        assert!(!blank(&doc, &jpeg_image(&[0], None, None)));
+    }
+
+    #[test]
+    fn adobe_cmyk_jpeg_is_stored_inverted() {
+        let doc = Document::with_version("1.7");
+        assert!(has_adobe_marker(&solid_jpeg(&[255; 4], Some(0))));
+        assert!(!has_adobe_marker(&solid_jpeg(&[255; 4], None)));
+        // Adobe 표식: 255 = 잉크 없음
+        assert!(blank(&doc, &jpeg_image(&[255; 4], Some(0), None)));
+        assert!(!blank(&doc, &jpeg_image(&[255, 255, 255, 0], Some(0), None)));
+        // 표식 없음: 0 = 잉크 없음
+        assert!(blank(&doc, &jpeg_image(&[0; 4], None, None)));
+        assert!(!blank(&doc, &jpeg_image(&[255; 4], None, None)));
+    }
+
+    #[test]
+    fn adobe_decode_array_applies_after_inversion() {
+        let doc = Document::with_version("1.7");
+        // 반전 저장 + /Decode [1 0 …] = 다시 뒤집혀 255가 잉크
+        let decode = Some((0..4).flat_map(|_| [1.into(), 0.into()]).collect());
+        assert!(!blank(&doc, &jpeg_image(&[255; 4], Some(0), decode.clone())));
+        assert!(blank(&doc, &jpeg_image(&[0; 4], Some(0), decode)));
+    }
+
+    #[test]
+    fn ycck_jpeg_is_converted_then_inverted() {
+        let doc = Document::with_version("1.7");
+        // Y = 0 → RGB 검정 → CMY 255 (반전 저장이므로 잉크 없음), K 255 = 잉크 없음
+        assert!(blank(&doc, &jpeg_image(&[0, 128, 128, 255], Some(2), None)));
+        // Y = 255 → CMY 0 → 잉크 가득
+        assert!(!blank(&doc, &jpeg_image(&[255, 128, 128, 255], Some(2), None)));
+        // K만 칠함
+        assert!(!blank(&doc, &jpeg_image(&[0, 128, 128, 0], Some(2), None)));
+    }
+
+    #[test]
+    fn soft_mask_hides_ink() {
+        let mut doc = Document::with_version("1.7");
+        let hidden = doc.add_object(gray_image(vec![0; 4], 2));
+        let shown = doc.add_object(gray_image(vec![0, 0, 0, 255], 2));
+
+        let mut image = gray_image(vec![0, 255, 255, 255], 2);
+        image.dict.set("SMask", hidden);
+        assert!(blank(&doc, &image));
+        // 알파가 살아 있는 픽셀은 흰색뿐
+        image.content = vec![255, 255, 255, 0];
+        image.dict.set("SMask", shown);
+        assert!(!blank(&doc, &image));
+        image.content = vec![0, 255, 255, 255];
+        assert!(blank(&doc, &image));
+    }
+
+    #[test]
+    fn ycc_to_rgb_matches_jfif() {
+        assert_eq!(ycc_to_rgb(0, 128, 128), [0, 0, 0]);
+        assert_eq!(ycc_to_rgb(255, 128, 128), [255, 255, 255]);
+        assert_eq!(ycc_to_rgb(76, 85, 255), [254, 0, 0]);
+    }
+}
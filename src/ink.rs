@@ -0,0 +1,1016 @@
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use lopdf::content::Content;
+use crate::image_ink;
+use crate::process_pages::{as_f64, effective_page_box, effective_resources, obj_as_dict_owned, page_content_streams};
+
+/// (x0, y0, x1, y1)
+pub type Aabb = (f64, f64, f64, f64);
+
+/// PDF 행렬 [a b c d e f] (행벡터 규약: p' = p × M)
+type Matrix = [f64; 6];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// Form XObject 중첩 한도 (순환 참조 방지)
+const MAX_FORM_DEPTH: usize = 16;
+
+// ========== 행렬 / AABB 유틸 ==========
+/// m을 먼저 적용한 뒤 n 적용 (m × n)
+fn mat_mul(m: &Matrix, n: &Matrix) -> Matrix {
+    [
+        m[0] * n[0] + m[1] * n[2],
+        m[0] * n[1] + m[1] * n[3],
+        m[2] * n[0] + m[3] * n[2],
+        m[2] * n[1] + m[3] * n[3],
+        m[4] * n[0] + m[5] * n[2] + n[4],
+        m[4] * n[1] + m[5] * n[3] + n[5],
+    ]
+}
+
+#[inline]
+fn apply(m: &Matrix, x: f64, y: f64) -> (f64, f64) {
+    (x * m[0] + y * m[2] + m[4], x * m[1] + y * m[3] + m[5])
+}
+
+fn transform_aabb(m: &Matrix, (x0, y0, x1, y1): Aabb) -> Aabb {
+    let pts = [apply(m, x0, y0), apply(m, x1, y0), apply(m, x0, y1), apply(m, x1, y1)];
+    aabb_of(&pts).expect("four corners")
+}
+
+fn aabb_of(pts: &[(f64, f64)]) -> Option<Aabb> {
+    let mut it = pts.iter();
+    let &(x, y) = it.next()?;
+    let mut bb = (x, y, x, y);
+    for &(x, y) in it {
+        bb = (bb.0.min(x), bb.1.min(y), bb.2.max(x), bb.3.max(y));
+    }
+    Some(bb)
+}
+
+fn union(a: Option<Aabb>, b: Aabb) -> Aabb {
+    match a {
+        Some(a) => (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)),
+        None => b,
+    }
+}
+
+fn intersect(a: Aabb, b: Aabb) -> Option<Aabb> {
+    let r = (a.0.max(b.0), a.1.max(b.1), a.2.min(b.2), a.3.min(b.3));
+    if r.2 < r.0 || r.3 < r.1 { None } else { Some(r) }
+}
+
+fn matrix_operand(ops: &[Object]) -> Option<Matrix> {
+    if ops.len() < 6 {
+        return None;
+    }
+    let mut m = [0.0; 6];
+    for (i, v) in m.iter_mut().enumerate() {
+        *v = as_f64(&ops[i])?;
+    }
+    Some(m)
+}
+
+fn matrix_object(obj: Option<&Object>) -> Matrix {
+    match obj {
+        Some(Object::Array(a)) => matrix_operand(a).unwrap_or(IDENTITY),
+        _ => IDENTITY,
+    }
+}
+
+fn rect_object(obj: Option<&Object>) -> Option<Aabb> {
+    let a = obj?.as_array().ok()?;
+    if a.len() != 4 {
+        return None;
+    }
+    let (x0, y0, x1, y1) = (as_f64(&a[0])?, as_f64(&a[1])?, as_f64(&a[2])?, as_f64(&a[3])?);
+    Some((x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)))
+}
+
+/// 3차 베지어의 한 축 극값 후보 t (0..1)
+fn cubic_extrema(p0: f64, p1: f64, p2: f64, p3: f64) -> Vec<f64> {
+    // B'(t) = 3[(p1-p0)(1-t)^2 + 2(p2-p1)t(1-t) + (p3-p2)t^2] = 0 → at^2 + bt + c = 0
+    let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+    let b = 2.0 * (p0 - 2.0 * p1 + p2);
+    let c = p1 - p0;
+    let mut ts = Vec::new();
+    if a.abs() < 1e-12 {
+        if b.abs() > 1e-12 {
+            ts.push(-c / b);
+        }
+    } else {
+        let disc = b * b - 4.0 * a * c;
+        if disc >= 0.0 {
+            let sq = disc.sqrt();
+            ts.push((-b + sq) / (2.0 * a));
+            ts.push((-b - sq) / (2.0 * a));
+        }
+    }
+    ts.retain(|t| *t > 0.0 && *t < 1.0);
+    ts
+}
+
+fn cubic_at(p0: f64, p1: f64, p2: f64, p3: f64, t: f64) -> f64 {
+    let mt = 1.0 - t;
+    mt * mt * mt * p0 + 3.0 * mt * mt * t * p1 + 3.0 * mt * t * t * p2 + t * t * t * p3
+}
+
+// ========== 폰트 메트릭 (글자 폭/높이 근사) ==========
+#[derive(Clone)]
+struct FontMetrics {
+    /// Type0(CID) 폰트면 2바이트 코드로 가정 (Identity-H 등)
+    two_byte: bool,
+    first_char: i64,
+    widths: Vec<f64>,
+    /// Type0 /W 배열 (cid 범위별 폭)
+    cid_widths: Vec<(i64, i64, f64)>,
+    default_width: f64,
+    /// em 대비 (1/1000 단위 아님)
+    ascent: f64,
+    descent: f64,
+    /// Type3: 글리프 공간 → 텍스트 공간 폭 배율(×1000)
+    width_scale: f64,
+}
+
+impl FontMetrics {
+    fn fallback() -> FontMetrics {
+        FontMetrics {
+            two_byte: false,
+            first_char: 0,
+            widths: Vec::new(),
+            cid_widths: Vec::new(),
+            default_width: 600.0,
+            ascent: 0.9,
+            descent: -0.25,
+            width_scale: 1.0,
+        }
+    }
+
+    fn load(doc: &Document, font: &Dictionary) -> FontMetrics {
+        let mut fm = FontMetrics::fallback();
+        let get_dict = |d: &Dictionary, k: &[u8]| d.get(k).ok().and_then(|o| obj_as_dict_owned(o, doc));
+        let subtype = font.get(b"Subtype").and_then(Object::as_name).unwrap_or(b"");
+
+        let mut descriptor = get_dict(font, b"FontDescriptor");
+        if subtype == b"Type0" {
+            fm.two_byte = true;
+            fm.default_width = 1000.0;
+            let desc = font.get(b"DescendantFonts").ok()
+                .and_then(|o| match o {
+                    Object::Reference(id) => doc.get_object(*id).ok(),
+                    o => Some(o),
+                })
+                .and_then(|o| o.as_array().ok())
+                .and_then(|a| a.first())
+                .and_then(|o| obj_as_dict_owned(o, doc));
+            if let Some(cid) = desc {
+                if let Ok(dw) = cid.get(b"DW") {
+                    fm.default_width = as_f64(dw).unwrap_or(1000.0);
+                }
+                if let Ok(Object::Array(w)) = cid.get(b"W") {
+                    fm.cid_widths = parse_cid_widths(w);
+                }
+                descriptor = get_dict(&cid, b"FontDescriptor");
+            }
+        } else {
+            fm.first_char = font.get(b"FirstChar").ok().and_then(as_f64).unwrap_or(0.0) as i64;
+            let widths = match font.get(b"Widths") {
+                Ok(Object::Array(a)) => Some(a.clone()),
+                Ok(Object::Reference(id)) => doc.get_object(*id).ok().and_then(|o| o.as_array().ok()).cloned(),
+                _ => None,
+            };
+            if let Some(w) = widths {
+                fm.widths = w.iter().map(|o| as_f64(o).unwrap_or(0.0)).collect();
+            }
+        }
+
+        if subtype == b"Type3" {
+            let fmx = matrix_object(font.get(b"FontMatrix").ok());
+            fm.width_scale = fmx[0] * 1000.0;
+            if let Some((_, y0, _, y1)) = rect_object(font.get(b"FontBBox").ok()) {
+                fm.ascent = y1 * fmx[3];
+                fm.descent = y0 * fmx[3];
+            }
+            return fm;
+        }
+
+        if let Some(d) = descriptor {
+            if let Ok(mw) = d.get(b"MissingWidth") {
+                fm.default_width = as_f64(mw).unwrap_or(fm.default_width);
+            }
+            let asc = d.get(b"Ascent").ok().and_then(as_f64).unwrap_or(0.0);
+            let desc = d.get(b"Descent").ok().and_then(as_f64).unwrap_or(0.0);
+            if asc > 0.0 {
+                fm.ascent = asc / 1000.0;
+                fm.descent = desc.min(0.0) / 1000.0;
+            } else if let Some((_, y0, _, y1)) = rect_object(d.get(b"FontBBox").ok()) {
+                fm.ascent = y1 / 1000.0;
+                fm.descent = y0 / 1000.0;
+            }
+        }
+        fm
+    }
+
+    /// 코드 → 글자 폭 (1/1000 em)
+    fn width(&self, code: i64) -> f64 {
+        if self.two_byte {
+            for &(lo, hi, w) in &self.cid_widths {
+                if code >= lo && code <= hi {
+                    return w;
+                }
+            }
+            return self.default_width;
+        }
+        let idx = code - self.first_char;
+        if idx >= 0 && (idx as usize) < self.widths.len() {
+            self.widths[idx as usize] * self.width_scale
+        } else {
+            self.default_width * self.width_scale
+        }
+    }
+
+    fn codes(&self, bytes: &[u8]) -> Vec<i64> {
+        if self.two_byte {
+            bytes.chunks(2).map(|c| c.iter().fold(0i64, |acc, b| (acc << 8) | *b as i64)).collect()
+        } else {
+            bytes.iter().map(|b| *b as i64).collect()
+        }
+    }
+}
+
+/// /W 배열: `c [w1 w2 ...]` 또는 `c_first c_last w`
+fn parse_cid_widths(w: &[Object]) -> Vec<(i64, i64, f64)> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < w.len() {
+        let Some(first) = as_f64(&w[i]).map(|v| v as i64) else { break };
+        match w.get(i + 1) {
+            Some(Object::Array(ws)) => {
+                for (k, o) in ws.iter().enumerate() {
+                    let c = first + k as i64;
+                    out.push((c, c, as_f64(o).unwrap_or(0.0)));
+                }
+                i += 2;
+            }
+            Some(last) => {
+                let last = as_f64(last).unwrap_or(first as f64) as i64;
+                let width = w.get(i + 2).and_then(as_f64).unwrap_or(0.0);
+                out.push((first, last, width));
+                i += 3;
+            }
+            None => break,
+        }
+    }
+    out
+}
+
+// ========== 색 ==========
+/// 색이 종이색(흰색)인지 판정하기 위한 색공간 분류
+#[derive(Clone, Copy, PartialEq)]
+enum ColorKind {
+    Gray,
+    Rgb,
+    Cmyk,
+    /// Separation / DeviceN: 모든 tint가 0이면 잉크 없음
+    Tint,
+    /// Pattern / Indexed / Lab 등: 항상 보이는 것으로 취급
+    Unknown,
+}
+
+/// 흰색으로 볼 성분 허용 오차 (8비트 기준 반 단계)
+const WHITE_EPS: f64 = 0.002;
+
+impl ColorKind {
+    fn is_white(self, comps: &[f64]) -> bool {
+        match self {
+            ColorKind::Gray | ColorKind::Rgb => !comps.is_empty() && comps.iter().all(|c| *c >= 1.0 - WHITE_EPS),
+            ColorKind::Cmyk | ColorKind::Tint => !comps.is_empty() && comps.iter().all(|c| *c <= WHITE_EPS),
+            ColorKind::Unknown => false,
+        }
+    }
+}
+
+/// 채우기/선 색 상태 (흰색 여부 + 불투명도만 추적)
+#[derive(Clone, Copy)]
+struct PaintState {
+    kind: ColorKind,
+    white: bool,
+    alpha: f64,
+}
+
+impl PaintState {
+    fn visible(&self) -> bool {
+        !self.white && self.alpha > 0.0
+    }
+}
+
+/// 마크가 어떤 색으로 칠해지는지
+#[derive(Clone, Copy)]
+enum Painted {
+    Fill,
+    Stroke,
+    FillStroke,
+    /// 이미지 / 셰이딩: 자체 색을 쓰므로 채우기 불투명도만 적용
+    Image,
+}
+
+// ========== 그래픽 상태 ==========
+/// 장치 공간 클립 (AABB 근사)
+#[derive(Clone, Copy)]
+enum Clip {
+    /// 제한 없음
+    Unbounded,
+    Rect(Aabb),
+    /// 교집합이 비어 아무것도 보이지 않음
+    Empty,
+}
+
+impl Clip {
+    /// 클립을 `bb`로 더 좁힘. 면적이 0이 되면 Empty
+    fn restrict(self, bb: Aabb) -> Clip {
+        let c = match self {
+            Clip::Unbounded => bb,
+            Clip::Rect(c) => (c.0.max(bb.0), c.1.max(bb.1), c.2.min(bb.2), c.3.min(bb.3)),
+            Clip::Empty => return Clip::Empty,
+        };
+        if c.2 <= c.0 || c.3 <= c.1 { Clip::Empty } else { Clip::Rect(c) }
+    }
+
+    /// 마크 `bb` 중 클립 안에서 보이는 부분
+    fn visible(self, bb: Aabb) -> Option<Aabb> {
+        match self {
+            Clip::Unbounded => Some(bb),
+            Clip::Rect(c) => intersect(bb, c),
+            Clip::Empty => None,
+        }
+    }
+
+    /// 클립 범위 전체 (제한이 없으면 `page_box`)
+    fn area(self, page_box: Aabb) -> Option<Aabb> {
+        match self {
+            Clip::Unbounded => Some(page_box),
+            Clip::Rect(c) => Some(c),
+            Clip::Empty => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct GState {
+    ctm: Matrix,
+    clip: Clip,
+    line_width: f64,
+    font: Option<FontMetrics>,
+    font_size: f64,
+    char_spacing: f64,
+    word_spacing: f64,
+    h_scale: f64,
+    leading: f64,
+    rise: f64,
+    render_mode: i64,
+    fill: PaintState,
+    stroke: PaintState,
+}
+
+impl GState {
+    fn new(ctm: Matrix) -> GState {
+        GState {
+            ctm,
+            clip: Clip::Unbounded,
+            line_width: 1.0,
+            font: None,
+            font_size: 0.0,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            h_scale: 1.0,
+            leading: 0.0,
+            rise: 0.0,
+            render_mode: 0,
+            fill: PaintState { kind: ColorKind::Gray, white: false, alpha: 1.0 },
+            stroke: PaintState { kind: ColorKind::Gray, white: false, alpha: 1.0 },
+        }
+    }
+}
+
+/// 한 번의 해석으로 함께 구하는 잉크 AABB (장치 공간, 페이지 박스와 교차)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PageInk {
+    /// 색/투명도와 관계없이 칠해진 모든 마크
+    pub raw: Option<Aabb>,
+    /// 눈에 보이는 마크. 이미지 허용 오차가 있으면 흰색/완전 투명 이미지도 제외
+    pub visible: Option<Aabb>,
+    /// 눈에 보이는 마크, 이미지는 디코딩하지 않고 항상 잉크로 봄
+    pub visible_with_images: Option<Aabb>,
+}
+
+/// 콘텐츠 스트림 해석기: 실제로 칠해지는 모든 마크의 장치 공간 AABB 합집합을 계산
+struct InkInterpreter<'a> {
+    doc: &'a Document,
+    /// `sh`처럼 클립 범위를 전부 칠하는 연산의 상한 (페이지 박스)
+    page_box: Aabb,
+    /// 이미지를 디코딩해 검사할 허용 오차 (None이면 이미지는 항상 잉크)
+    image_tolerance: Option<f64>,
+    ink: PageInk,
+}
+
+impl<'a> InkInterpreter<'a> {
+    fn mark(&mut self, gs: &GState, bb: Aabb, painted: Painted) {
+        self.paint(gs, bb, painted, false);
+    }
+
+    /// `blank_image`: 디코딩해 보니 흰색/투명인 이미지 (`visible`에만 넣지 않음)
+    fn paint(&mut self, gs: &GState, bb: Aabb, painted: Painted, blank_image: bool) {
+        let Some(bb) = gs.clip.visible(bb) else { return };
+        self.ink.raw = Some(union(self.ink.raw, bb));
+        let visible = match painted {
+            Painted::Fill => gs.fill.visible(),
+            Painted::Stroke => gs.stroke.visible(),
+            Painted::FillStroke => gs.fill.visible() || gs.stroke.visible(),
+            Painted::Image => gs.fill.alpha > 0.0,
+        };
+        if visible {
+            self.ink.visible_with_images = Some(union(self.ink.visible_with_images, bb));
+            if !blank_image {
+                self.ink.visible = Some(union(self.ink.visible, bb));
+            }
+        }
+    }
+
+    /// 단위 정사각형에 그려지는 이미지. 스텐실 마스크는 채우기 색으로 칠해짐.
+    /// 디코딩은 이미지가 실제로 보일 때만 한다
+    fn image(&mut self, gs: &GState, image: &Stream, resources: &Option<Dictionary>) {
+        let bb = transform_aabb(&gs.ctm, (0.0, 0.0, 1.0, 1.0));
+        let stencil = image.dict.get(b"ImageMask").or_else(|_| image.dict.get(b"IM"))
+            .and_then(Object::as_bool).unwrap_or(false);
+        if stencil {
+            return self.mark(gs, bb, Painted::Fill);
+        }
+        let blank = gs.fill.alpha > 0.0
+            && gs.clip.visible(bb).is_some()
+            && self.image_tolerance
+                .is_some_and(|tolerance| image_ink::image_is_blank(self.doc, image, resources, tolerance));
+        self.paint(gs, bb, Painted::Image, blank);
+    }
+
+    fn run(&mut self, content: &[u8], resources: &Option<Dictionary>, gs: GState, depth: usize) {
+        let Ok(content) = Content::decode(content) else {
+            // 해석할 수 없는 스트림: 빈 페이지 판정에서는 클립 전체를 잉크로 봄 (잘못 지우지 않도록)
+            if let Some(area) = gs.clip.area(self.page_box) {
+                self.ink.visible = Some(union(self.ink.visible, area));
+                self.ink.visible_with_images = Some(union(self.ink.visible_with_images, area));
+            }
+            return;
+        };
+
+        let mut gs = gs;
+        let mut stack: Vec<GState> = Vec::new();
+        // 경로: 장치 공간 점 목록 + 현재점/서브패스 시작점(사용자 공간)
+        let mut path: Vec<(f64, f64)> = Vec::new();
+        let mut cur = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        let mut pending_clip = false;
+        // 텍스트 행렬
+        let mut tm = IDENTITY;
+        let mut tlm = IDENTITY;
+
+        for op in &content.operations {
+            let o = &op.operands;
+            let num = |i: usize| o.get(i).and_then(as_f64).unwrap_or(0.0);
+            match op.operator.as_str() {
+                // --- 그래픽 상태 ---
+                "q" => stack.push(gs.clone()),
+                "Q" => {
+                    if let Some(g) = stack.pop() {
+                        gs = g;
+                    }
+                }
+                "cm" => {
+                    if let Some(m) = matrix_operand(o) {
+                        gs.ctm = mat_mul(&m, &gs.ctm);
+                    }
+                }
+                "w" => gs.line_width = num(0),
+                "gs" => {
+                    if let Some(Object::Name(n)) = o.first()
+                        && let Some(egs) = lookup_resource(self.doc, resources, b"ExtGState", n)
+                    {
+                        if let Ok(lw) = egs.get(b"LW") {
+                            gs.line_width = as_f64(lw).unwrap_or(gs.line_width);
+                        }
+                        if let Some(ca) = egs.get(b"ca").ok().and_then(as_f64) {
+                            gs.fill.alpha = ca;
+                        }
+                        if let Some(ca) = egs.get(b"CA").ok().and_then(as_f64) {
+                            gs.stroke.alpha = ca;
+                        }
+                    }
+                }
+
+                // --- 색 ---
+                "g" | "G" | "rg" | "RG" | "k" | "K" => {
+                    let kind = match op.operator.as_str() {
+                        "g" | "G" => ColorKind::Gray,
+                        "rg" | "RG" => ColorKind::Rgb,
+                        _ => ColorKind::Cmyk,
+                    };
+                    let comps: Vec<f64> = o.iter().filter_map(as_f64).collect();
+                    let paint = if op.operator.chars().all(|c| c.is_ascii_lowercase()) { &mut gs.fill } else { &mut gs.stroke };
+                    paint.kind = kind;
+                    paint.white = kind.is_white(&comps);
+                }
+                "cs" | "CS" => {
+                    let kind = match o.first() {
+                        Some(Object::Name(n)) => color_space_kind(self.doc, resources, n),
+                        _ => ColorKind::Unknown,
+                    };
+                    let paint = if op.operator == "cs" { &mut gs.fill } else { &mut gs.stroke };
+                    // 초기 색은 검정 / tint 1.0
+                    paint.kind = kind;
+                    paint.white = false;
+                }
+                "sc" | "scn" | "SC" | "SCN" => {
+                    let paint = if op.operator.starts_with('s') { &mut gs.fill } else { &mut gs.stroke };
+                    // 패턴 이름이 붙으면 색을 알 수 없음
+                    paint.white = !matches!(o.last(), Some(Object::Name(_)))
+                        && paint.kind.is_white(&o.iter().filter_map(as_f64).collect::<Vec<_>>());
+                }
+
+                // --- 경로 구성 ---
+                "m" => {
+                    cur = (num(0), num(1));
+                    start = cur;
+                    path.push(apply(&gs.ctm, cur.0, cur.1));
+                }
+                "l" => {
+                    cur = (num(0), num(1));
+                    path.push(apply(&gs.ctm, cur.0, cur.1));
+                }
+                "c" | "v" | "y" => {
+                    let (p1, p2, p3) = match op.operator.as_str() {
+                        "c" => ((num(0), num(1)), (num(2), num(3)), (num(4), num(5))),
+                        "v" => (cur, (num(0), num(1)), (num(2), num(3))),
+                        _ => ((num(0), num(1)), (num(2), num(3)), (num(2), num(3))),
+                    };
+                    let d = [cur, p1, p2, p3].map(|(x, y)| apply(&gs.ctm, x, y));
+                    path.push(d[3]);
+                    for t in cubic_extrema(d[0].0, d[1].0, d[2].0, d[3].0)
+                        .into_iter()
+                        .chain(cubic_extrema(d[0].1, d[1].1, d[2].1, d[3].1))
+                    {
+                        path.push((
+                            cubic_at(d[0].0, d[1].0, d[2].0, d[3].0, t),
+                            cubic_at(d[0].1, d[1].1, d[2].1, d[3].1, t),
+                        ));
+                    }
+                    cur = p3;
+                }
+                "h" => cur = start,
+                "re" => {
+                    let (x, y, w, h) = (num(0), num(1), num(2), num(3));
+                    for (px, py) in [(x, y), (x + w, y), (x + w, y + h), (x, y + h)] {
+                        path.push(apply(&gs.ctm, px, py));
+                    }
+                    cur = (x, y);
+                    start = cur;
+                }
+                "W" | "W*" => pending_clip = true,
+
+                // --- 경로 칠하기 ---
+                "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" | "n" => {
+                    let name = op.operator.as_str();
+                    if let Some(bb) = aabb_of(&path) {
+                        let stroked = matches!(name, "S" | "s" | "B" | "B*" | "b" | "b*");
+                        let painted = match name {
+                            "S" | "s" => Painted::Stroke,
+                            "B" | "B*" | "b" | "b*" => Painted::FillStroke,
+                            _ => Painted::Fill,
+                        };
+                        if name != "n" {
+                            let bb = if stroked { expand_by_stroke(&gs, bb) } else { bb };
+                            self.mark(&gs, bb, painted);
+                        }
+                        if pending_clip {
+                            gs.clip = gs.clip.restrict(bb);
+                        }
+                    } else if pending_clip {
+                        // 빈 경로로 클립: 아무것도 보이지 않음
+                        gs.clip = Clip::Empty;
+                    }
+                    path.clear();
+                    pending_clip = false;
+                }
+
+                // --- 셰이딩: 현재 클립 전체(또는 셰이딩 BBox) ---
+                "sh" => {
+                    let Some(mut bb) = gs.clip.area(self.page_box) else { continue };
+                    if let Some(Object::Name(n)) = o.first()
+                        && let Some(sh) = lookup_resource(self.doc, resources, b"Shading", n)
+                        && let Some(sbb) = rect_object(sh.get(b"BBox").ok())
+                    {
+                        match intersect(bb, transform_aabb(&gs.ctm, sbb)) {
+                            Some(b) => bb = b,
+                            None => continue,
+                        }
+                    }
+                    self.mark(&gs, bb, Painted::Image);
+                }
+
+                // --- 이미지 / XObject ---
+                "BI" => {
+                    if let Some(Object::Stream(image)) = o.first() {
+                        self.image(&gs, image, resources);
+                    }
+                }
+                "Do" => {
+                    let Some(Object::Name(n)) = o.first() else { continue };
+                    let Some(xobj) = lookup_xobject(self.doc, resources, n) else { continue };
+                    match xobj.dict.get(b"Subtype").and_then(Object::as_name) {
+                        Ok(b"Image") => self.image(&gs, xobj, resources),
+                        Ok(b"Form") if depth < MAX_FORM_DEPTH => {
+                            let mut inner = gs.clone();
+                            inner.ctm = mat_mul(&matrix_object(xobj.dict.get(b"Matrix").ok()), &gs.ctm);
+                            if let Some(bbox) = rect_object(xobj.dict.get(b"BBox").ok()) {
+                                let dev = transform_aabb(&inner.ctm, bbox);
+                                inner.clip = inner.clip.restrict(dev);
+                                if let Clip::Empty = inner.clip {
+                                    continue;
+                                }
+                            }
+                            // Form 전용 Resources 우선
+                            let frm_res = match xobj.dict.get(b"Resources") {
+                                Ok(r) => obj_as_dict_owned(r, self.doc),
+                                Err(_) => resources.clone(),
+                            };
+                            let bytes = xobj.get_plain_content().unwrap_or_else(|_| xobj.content.clone());
+                            self.run(&bytes, &frm_res, inner, depth + 1);
+                        }
+                        _ => {}
+                    }
+                }
+
+                // --- 텍스트 ---
+                "BT" => {
+                    tm = IDENTITY;
+                    tlm = IDENTITY;
+                }
+                "Tf" => {
+                    if let Some(Object::Name(n)) = o.first() {
+                        gs.font = Some(match lookup_resource(self.doc, resources, b"Font", n) {
+                            Some(f) => FontMetrics::load(self.doc, &f),
+                            None => FontMetrics::fallback(),
+                        });
+                    }
+                    gs.font_size = num(1);
+                }
+                "Tc" => gs.char_spacing = num(0),
+                "Tw" => gs.word_spacing = num(0),
+                "Tz" => gs.h_scale = num(0) / 100.0,
+                "TL" => gs.leading = num(0),
+                "Ts" => gs.rise = num(0),
+                "Tr" => gs.render_mode = num(0) as i64,
+                "Td" | "TD" => {
+                    if op.operator == "TD" {
+                        gs.leading = -num(1);
+                    }
+                    tlm = mat_mul(&[1.0, 0.0, 0.0, 1.0, num(0), num(1)], &tlm);
+                    tm = tlm;
+                }
+                "Tm" => {
+                    if let Some(m) = matrix_operand(o) {
+                        tlm = m;
+                        tm = m;
+                    }
+                }
+                "T*" => {
+                    tlm = mat_mul(&[1.0, 0.0, 0.0, 1.0, 0.0, -gs.leading], &tlm);
+                    tm = tlm;
+                }
+                "Tj" | "'" | "\"" | "TJ" => {
+                    if op.operator == "\"" {
+                        gs.word_spacing = num(0);
+                        gs.char_spacing = num(1);
+                    }
+                    if matches!(op.operator.as_str(), "'" | "\"") {
+                        tlm = mat_mul(&[1.0, 0.0, 0.0, 1.0, 0.0, -gs.leading], &tlm);
+                        tm = tlm;
+                    }
+                    let items: Vec<&Object> = match op.operator.as_str() {
+                        "TJ" => match o.first() {
+                            Some(Object::Array(a)) => a.iter().collect(),
+                            _ => Vec::new(),
+                        },
+                        _ => o.last().into_iter().collect(),
+                    };
+                    self.show_text(&gs, &mut tm, &items);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 문자열(및 TJ 간격) 출력: 글자 영역 AABB 마킹 + 텍스트 행렬 전진
+    fn show_text(&mut self, gs: &GState, tm: &mut Matrix, items: &[&Object]) {
+        let fm = gs.font.clone().unwrap_or_else(FontMetrics::fallback);
+        let (fs, th) = (gs.font_size, gs.h_scale);
+        let mut x = 0.0_f64;
+        let mut run: Option<(f64, f64)> = None;
+
+        for item in items {
+            match item {
+                Object::String(bytes, _) => {
+                    for code in fm.codes(bytes) {
+                        let w = fm.width(code) / 1000.0 * fs * th;
+                        let (a, b) = (x.min(x + w), x.max(x + w));
+                        run = Some(match run {
+                            Some((r0, r1)) => (r0.min(a), r1.max(b)),
+                            None => (a, b),
+                        });
+                        let ws = if !fm.two_byte && code == 32 { gs.word_spacing } else { 0.0 };
+                        x += (fm.width(code) / 1000.0 * fs + gs.char_spacing + ws) * th;
+                    }
+                }
+                other => {
+                    if let Some(adj) = as_f64(other) {
+                        x -= adj / 1000.0 * fs * th;
+                    }
+                }
+            }
+        }
+
+        // 3 = 보이지 않음, 7 = 클립 전용 → 잉크 없음
+        let painted = match gs.render_mode {
+            0 | 4 => Some(Painted::Fill),
+            1 | 5 => Some(Painted::Stroke),
+            2 | 6 => Some(Painted::FillStroke),
+            _ => None,
+        };
+        if let Some((x0, x1)) = run
+            && let Some(painted) = painted
+        {
+            let y0 = fm.descent * fs + gs.rise;
+            let y1 = fm.ascent * fs + gs.rise;
+            let trm = mat_mul(tm, &gs.ctm);
+            self.mark(gs, transform_aabb(&trm, (x0, y0.min(y1), x1, y0.max(y1))), painted);
+        }
+        *tm = mat_mul(&[1.0, 0.0, 0.0, 1.0, x, 0.0], tm);
+    }
+}
+
+/// 선폭 절반만큼 (장치 공간으로 변환해) 확장
+fn expand_by_stroke(gs: &GState, (x0, y0, x1, y1): Aabb) -> Aabb {
+    let hw = gs.line_width.max(0.0) / 2.0;
+    let m = &gs.ctm;
+    let dx = hw * (m[0].abs() + m[2].abs());
+    let dy = hw * (m[1].abs() + m[3].abs());
+    (x0 - dx, y0 - dy, x1 + dx, y1 + dy)
+}
+
+fn lookup_resource(doc: &Document, resources: &Option<Dictionary>, category: &[u8], name: &[u8]) -> Option<Dictionary> {
+    let res = resources.as_ref()?;
+    let cat = obj_as_dict_owned(res.get(category).ok()?, doc)?;
+    obj_as_dict_owned(cat.get(name).ok()?, doc)
+}
+
+/// 이름 있는 색공간 → 분류 (장치 색공간 이름 또는 /ColorSpace 리소스)
+fn color_space_kind(doc: &Document, resources: &Option<Dictionary>, name: &[u8]) -> ColorKind {
+    match name {
+        b"DeviceGray" | b"G" | b"CalGray" => return ColorKind::Gray,
+        b"DeviceRGB" | b"RGB" | b"CalRGB" => return ColorKind::Rgb,
+        b"DeviceCMYK" | b"CMYK" => return ColorKind::Cmyk,
+        b"Pattern" => return ColorKind::Unknown,
+        _ => {}
+    }
+    let Some(cs) = resources.as_ref()
+        .and_then(|r| r.get(b"ColorSpace").ok())
+        .and_then(|c| obj_as_dict_owned(c, doc))
+        .and_then(|c| c.get(name).ok().cloned())
+    else {
+        return ColorKind::Unknown;
+    };
+    let cs = match cs {
+        Object::Reference(id) => doc.get_object(id).cloned().unwrap_or(Object::Null),
+        o => o,
+    };
+    match &cs {
+        Object::Name(n) if n.as_slice() != name => color_space_kind(doc, &None, n),
+        Object::Array(a) => match a.first().and_then(|f| f.as_name().ok()) {
+            Some(b"CalGray") => ColorKind::Gray,
+            Some(b"CalRGB") => ColorKind::Rgb,
+            Some(b"Separation" | b"DeviceN") => ColorKind::Tint,
+            Some(b"ICCBased") => {
+                let n = a.get(1)
+                    .and_then(|s| s.as_reference().ok())
+                    .and_then(|id| doc.get_object(id).ok())
+                    .and_then(|o| o.as_stream().ok())
+                    .and_then(|s| s.dict.get(b"N").ok())
+                    .and_then(as_f64);
+                match n {
+                    Some(1.0) => ColorKind::Gray,
+                    Some(3.0) => ColorKind::Rgb,
+                    Some(4.0) => ColorKind::Cmyk,
+                    _ => ColorKind::Unknown,
+                }
+            }
+            _ => ColorKind::Unknown,
+        },
+        _ => ColorKind::Unknown,
+    }
+}
+
+fn lookup_xobject<'d>(doc: &'d Document, resources: &Option<Dictionary>, name: &[u8]) -> Option<&'d Stream> {
+    let res = resources.as_ref()?;
+    let cat = obj_as_dict_owned(res.get(b"XObject").ok()?, doc)?;
+    match cat.get(name).ok()? {
+        Object::Reference(id) => doc.get_object(*id).ok()?.as_stream().ok(),
+        _ => None,
+    }
+}
+
+/// 페이지의 실잉크 AABB (페이지 박스와 교차). 그려진 것이 없으면 None
+pub fn page_ink_bbox(doc: &Document, page_id: ObjectId) -> Option<Aabb> {
+    page_ink(doc, page_id, None).raw
+}
+
+/// 페이지 잉크를 한 번에 해석. `visible`은 흰색 / 완전 투명(ca, CA = 0) / 보이지 않는 텍스트(3 Tr) /
+/// 페이지 밖으로 잘린 마크를 제외하며, None이면 시각적으로 빈 페이지.
+/// `image_tolerance`가 있으면 흰색/완전 투명 이미지도 `visible`에서 제외
+pub fn page_ink(doc: &Document, page_id: ObjectId, image_tolerance: Option<f64>) -> PageInk {
+    let Some(page_box) = effective_page_box(doc, page_id) else { return PageInk::default() };
+    let Ok(streams) = page_content_streams(doc, page_id) else { return PageInk::default() };
+
+    // 페이지 콘텐츠 스트림은 하나로 이어진 것처럼 해석
+    let mut concat = Vec::<u8>::new();
+    for s in &streams {
+        concat.extend_from_slice(&s.get_plain_content().unwrap_or_else(|_| s.content.clone()));
+        concat.push(b'\n');
+    }
+
+    let resources = effective_resources(doc, page_id);
+    let mut interp = InkInterpreter { doc, page_box, image_tolerance, ink: PageInk::default() };
+    interp.run(&concat, &resources, GState::new(IDENTITY), 0);
+    let clip = |bb: Option<Aabb>| bb.and_then(|bb| intersect(bb, page_box));
+    PageInk {
+        raw: clip(interp.ink.raw),
+        visible: clip(interp.ink.visible),
+        visible_with_images: clip(interp.ink.visible_with_images),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    /// 100 × 100 페이지 하나 (페이지 트리 없이 페이지 객체만)
+    fn page(content: &str, resources: Dictionary) -> (Document, ObjectId) {
+        let mut doc = Document::with_version("1.7");
+        let contents = doc.add_object(Stream::new(Dictionary::new(), content.as_bytes().to_vec()));
+        let page = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "MediaBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+            "Contents" => contents,
+            "Resources" => resources,
+        });
+        (doc, page)
+    }
+
+    fn ink_of(content: &str) -> PageInk {
+        let (doc, pid) = page(content, Dictionary::new());
+        page_ink(&doc, pid, None)
+    }
+
+    fn assert_bbox(bb: Option<Aabb>, expected: Aabb) {
+        let bb = bb.expect("no ink");
+        for (a, b) in [(bb.0, expected.0), (bb.1, expected.1), (bb.2, expected.2), (bb.3, expected.3)] {
+            assert!((a - b).abs() < 1e-6, "{bb:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn empty_page_has_no_ink() {
+        let ink = ink_of("");
+        assert!(ink.raw.is_none() && ink.visible.is_none() && ink.visible_with_images.is_none());
+    }
+
+    #[test]
+    fn ctm_scales_and_moves_marks() {
+        let ink = ink_of("q 2 0 0 2 10 10 cm 0 0 5 5 re f Q 50 50 1 1 re f");
+        assert_bbox(ink.raw, (10.0, 10.0, 51.0, 51.0));
+    }
+
+    #[test]
+    fn restored_ctm_does_not_leak() {
+        let ink = ink_of("q 3 0 0 3 0 0 cm Q 1 1 2 2 re f");
+        assert_bbox(ink.raw, (1.0, 1.0, 3.0, 3.0));
+    }
+
+    #[test]
+    fn clip_limits_marks() {
+        assert_bbox(ink_of("0 0 10 10 re W n 0 0 50 50 re f").raw, (0.0, 0.0, 10.0, 10.0));
+        // 클립 밖에만 그린 마크는 잉크 아님
+        assert!(ink_of("0 0 10 10 re W n 20 20 5 5 re f").raw.is_none());
+        // 페이지 밖은 페이지 박스로 잘림
+        assert_bbox(ink_of("90 90 50 50 re f").raw, (90.0, 90.0, 100.0, 100.0));
+    }
+
+    #[test]
+    fn white_and_transparent_marks_are_not_visible() {
+        let white = ink_of("1 g 0 0 10 10 re f 1 1 1 RG 20 20 5 5 re S");
+        assert!(white.raw.is_some());
+        assert!(white.visible.is_none());
+
+        let (doc, pid) = page(
+            "/GS1 gs 0 0 10 10 re f",
+            dictionary! { "ExtGState" => dictionary! { "GS1" => dictionary! { "ca" => 0 } } },
+        );
+        let ink = page_ink(&doc, pid, None);
+        assert!(ink.raw.is_some());
+        assert!(ink.visible.is_none());
+
+        // 흰 바탕 위에 검은 마크 하나
+        assert_bbox(ink_of("1 g 0 0 100 100 re f 0 g 40 40 5 5 re f").visible, (40.0, 40.0, 45.0, 45.0));
+    }
+
+    #[test]
+    fn invisible_text_is_not_ink() {
+        let (doc, pid) = page(
+            "BT /F1 12 Tf 3 Tr 10 10 Td (hidden) Tj ET",
+            dictionary! { "Font" => dictionary! { "F1" => dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica" } } },
+        );
+        assert!(page_ink(&doc, pid, None).raw.is_none());
+    }
+
+    #[test]
+    fn form_xobjects_use_matrix_and_bbox() {
+        let mut doc = Document::with_version("1.7");
+        let inner = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject", "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            },
+            b"0 0 50 50 re f".to_vec(),
+        ));
+        // 바깥 폼이 안쪽 폼을 (20, 20)으로 옮겨 그림
+        let outer = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject", "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+                "Matrix" => vec![1.into(), 0.into(), 0.into(), 1.into(), 20.into(), 20.into()],
+                "Resources" => dictionary! { "XObject" => dictionary! { "In" => inner } },
+            },
+            b"/In Do".to_vec(),
+        ));
+        let contents = doc.add_object(Stream::new(Dictionary::new(), b"q 2 0 0 2 0 0 cm /Out Do Q".to_vec()));
+        let pid = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "MediaBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+            "Contents" => contents,
+            "Resources" => dictionary! { "XObject" => dictionary! { "Out" => outer } },
+        });
+        assert_bbox(page_ink(&doc, pid, None).raw, (40.0, 40.0, 60.0, 60.0));
+    }
+
+    #[test]
+    fn self_referencing_form_stops() {
+        let mut doc = Document::with_version("1.7");
+        let form = doc.new_object_id();
+        doc.objects.insert(form, Object::Stream(Stream::new(
+            dictionary! {
+                "Type" => "XObject", "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+                "Resources" => dictionary! { "XObject" => dictionary! { "Me" => form } },
+            },
+            b"0 0 1 1 re f /Me Do".to_vec(),
+        )));
+        let contents = doc.add_object(Stream::new(Dictionary::new(), b"/Me Do".to_vec()));
+        let pid = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "MediaBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+            "Contents" => contents,
+            "Resources" => dictionary! { "XObject" => dictionary! { "Me" => form } },
+        });
+        assert_bbox(page_ink(&doc, pid, None).raw, (0.0, 0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn white_images_are_visible_only_without_inspection() {
+        let mut doc = Document::with_version("1.7");
+        let image = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject", "Subtype" => "Image",
+                "Width" => 2, "Height" => 2, "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8,
+            },
+            vec![255; 4],
+        ));
+        let contents = doc.add_object(Stream::new(Dictionary::new(), b"q 10 0 0 10 5 5 cm /Im Do Q".to_vec()));
+        let pid = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "MediaBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+            "Contents" => contents,
+            "Resources" => dictionary! { "XObject" => dictionary! { "Im" => image } },
+        });
+        let ink = page_ink(&doc, pid, Some(0.02));
+        assert!(ink.visible.is_none());
+        assert_bbox(ink.visible_with_images, (5.0, 5.0, 15.0, 15.0));
+        assert_bbox(page_ink(&doc, pid, None).visible, (5.0, 5.0, 15.0, 15.0));
+    }
+}
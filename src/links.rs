@@ -0,0 +1,575 @@
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use crate::outline;
+
+/// 이름 트리 노드의 (키, 값) 전부 수집
+fn name_tree_entries(doc: &Document, node: &Object, out: &mut Vec<(Vec<u8>, Object)>, depth: usize) {
+    if depth > 32 {
+        return;
+    }
+    let Ok((_, node)) = doc.dereference(node) else { return };
+    let Ok(dict) = node.as_dict() else { return };
+    if let Ok(names) = dict.get(b"Names").and_then(|o| doc.dereference(o)).and_then(|(_, o)| o.as_array()) {
+        for pair in names.chunks(2) {
+            if let [Object::String(key, _), value] = pair {
+                out.push((key.clone(), value.clone()));
+            }
+        }
+    }
+    if let Ok(kids) = dict.get(b"Kids").and_then(|o| doc.dereference(o)).and_then(|(_, o)| o.as_array()) {
+        for kid in kids {
+            name_tree_entries(doc, kid, out, depth + 1);
+        }
+    }
+}
+
+/// 이름 트리에서 간접 참조로 된 노드 객체 (루트 포함). 값이 가리키는 목적지 객체는 넣지 않는다
+fn name_tree_nodes(doc: &Document, node: &Object, out: &mut Vec<ObjectId>, depth: usize) {
+    if depth > 32 {
+        return;
+    }
+    if let Object::Reference(id) = node {
+        out.push(*id);
+    }
+    let Ok((_, node)) = doc.dereference(node) else { return };
+    let Ok(kids) = node.as_dict().and_then(|d| d.get(b"Kids")).and_then(|o| doc.dereference(o)).and_then(|(_, o)| o.as_array()) else { return };
+    for kid in kids {
+        name_tree_nodes(doc, kid, out, depth + 1);
+    }
+}
+
+/// 카탈로그의 목적지 이름 트리(/Names /Dests)와 옛 /Dests 딕셔너리 객체. 트리를 새로 쓸 때 지울 것들
+fn dest_tree_objects(doc: &Document) -> Vec<ObjectId> {
+    let mut out = Vec::new();
+    let Ok(catalog) = doc.catalog() else { return out };
+    if let Ok(Object::Reference(id)) = catalog.get(b"Dests") {
+        out.push(*id);
+    }
+    if let Ok(names) = catalog.get(b"Names").and_then(|o| doc.dereference(o)).and_then(|(_, o)| o.as_dict())
+        && let Ok(tree) = names.get(b"Dests")
+    {
+        name_tree_nodes(doc, tree, &mut out, 0);
+    }
+    out
+}
+
+/// 문서의 이름 있는 목적지 전부: 카탈로그 /Dests(이름 키) + /Names /Dests(이름 트리)
+pub fn named_dests(doc: &Document) -> Vec<(Vec<u8>, Object)> {
+    let mut out = Vec::new();
+    let Ok(catalog) = doc.catalog() else { return out };
+    if let Ok(dests) = catalog.get(b"Dests").and_then(|o| doc.dereference(o)).and_then(|(_, o)| o.as_dict()) {
+        for (k, v) in dests.iter() {
+            out.push((k.clone(), v.clone()));
+        }
+    }
+    if let Ok(names) = catalog.get(b"Names").and_then(|o| doc.dereference(o)).and_then(|(_, o)| o.as_dict())
+        && let Ok(tree) = names.get(b"Dests")
+    {
+        name_tree_entries(doc, tree, &mut out, 0);
+    }
+    out
+}
+
+/// 이름 있는 목적지 → 목적지 배열
+pub fn resolve_named_dest(doc: &Document, name: &[u8]) -> Option<Object> {
+    let (_, found) = named_dests(doc).into_iter().find(|(k, _)| k.as_slice() == name)?;
+    // 값이 << /D [...] >> 형태일 수 있음
+    match doc.dereference(&found).ok()?.1 {
+        Object::Dictionary(d) => d.get(b"D").ok().cloned(),
+        o => Some(o.clone()),
+    }
+}
+
+/// 목적지(배열 / 이름) → 대상 페이지 객체
+pub fn dest_page(doc: &Document, dest: &Object) -> Option<ObjectId> {
+    match doc.dereference(dest).ok()?.1 {
+        Object::Array(a) => a.first()?.as_reference().ok(),
+        Object::Name(n) | Object::String(n, _) => {
+            let resolved = resolve_named_dest(doc, n)?;
+            resolved.as_array().ok()?.first()?.as_reference().ok()
+        }
+        _ => None,
+    }
+}
+
+/// 링크 주석 / 책갈피 항목의 /Dest 또는 /A GoTo 대상 페이지 (문서 내부 이동이 아니면 None)
+pub fn target_page(doc: &Document, item: &Dictionary) -> Option<ObjectId> {
+    if let Ok(dest) = item.get(b"Dest") {
+        return dest_page(doc, dest);
+    }
+    let action = doc.dereference(item.get(b"A").ok()?).ok()?.1.as_dict().ok()?;
+    if action.get(b"S").ok()?.as_name().ok()? != b"GoTo" {
+        return None;
+    }
+    dest_page(doc, action.get(b"D").ok()?)
+}
+
+/// 모든 페이지의 주석 객체 (간접 참조된 것만)
+fn annotation_ids(doc: &Document) -> Vec<ObjectId> {
+    let mut out = Vec::new();
+    for pid in doc.get_pages().values() {
+        let Ok(page) = doc.get_object(*pid).and_then(|o| o.as_dict()) else { continue };
+        let Ok(annots) = page.get(b"Annots").and_then(|o| doc.dereference(o)).and_then(|(_, o)| o.as_array()) else { continue };
+        out.extend(annots.iter().filter_map(|a| a.as_reference().ok()));
+    }
+    out
+}
+
+/// 이름 참조 하나를 문자열(이름 트리) 형식으로 바꾸고 `rename`이 있으면 새 이름 적용
+fn renamed(dest: &Object, rename: &HashMap<Vec<u8>, Vec<u8>>) -> Option<Object> {
+    let (Object::Name(n) | Object::String(n, _)) = dest else { return None };
+    let new = rename.get(n).unwrap_or(n);
+    Some(Object::string_literal(new.clone()))
+}
+
+/// 링크 주석과 책갈피 항목의 이름 참조(/Dest, /A GoTo /D)를 다시 쓴다
+fn rewrite_named_refs(doc: &mut Document, rename: &HashMap<Vec<u8>, Vec<u8>>) {
+    let mut holders = annotation_ids(doc);
+    holders.extend(outline::all_items(doc));
+
+    for id in holders {
+        let Ok(dict) = doc.get_object(id).and_then(|o| o.as_dict()) else { continue };
+        if let Ok(dest) = dict.get(b"Dest") {
+            if let Some(new) = renamed(dest, rename)
+                && let Ok(d) = doc.get_object_mut(id).and_then(|o| o.as_dict_mut())
+            {
+                d.set("Dest", new);
+            }
+            continue;
+        }
+        // /A: 인라인 딕셔너리 또는 간접 객체
+        let (action_id, new) = match dict.get(b"A") {
+            Ok(Object::Reference(aid)) => {
+                let new = doc.get_object(*aid).and_then(|o| o.as_dict()).ok()
+                    .filter(|a| a.get(b"S").and_then(|s| s.as_name()).ok() == Some(b"GoTo".as_slice()))
+                    .and_then(|a| a.get(b"D").ok())
+                    .and_then(|d| renamed(d, rename));
+                (Some(*aid), new)
+            }
+            Ok(Object::Dictionary(a)) => {
+                let new = Some(a)
+                    .filter(|a| a.get(b"S").and_then(|s| s.as_name()).ok() == Some(b"GoTo".as_slice()))
+                    .and_then(|a| a.get(b"D").ok())
+                    .and_then(|d| renamed(d, rename));
+                (None, new)
+            }
+            _ => continue,
+        };
+        let Some(new) = new else { continue };
+        let target = match action_id {
+            Some(aid) => doc.get_object_mut(aid).and_then(|o| o.as_dict_mut()),
+            None => doc.get_object_mut(id)
+                .and_then(|o| o.as_dict_mut())
+                .and_then(|d| d.get_mut(b"A"))
+                .and_then(|a| a.as_dict_mut()),
+        };
+        if let Ok(action) = target {
+            action.set("D", new);
+        }
+    }
+}
+
+/// 카탈로그 /Names /Dests를 `entries`로 된 평면 이름 트리로 교체하고 옛 /Dests 딕셔너리는 제거.
+/// 기존 트리 루트 객체가 있으면 그 번호에 덮어쓰고 나머지 노드는 지워 고아 객체를 남기지 않는다
+fn write_name_tree(doc: &mut Document, mut entries: Vec<(Vec<u8>, Object)>) -> Result<(), Box<dyn Error>> {
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut names = Vec::with_capacity(entries.len() * 2);
+    for (k, v) in entries {
+        names.push(Object::string_literal(k));
+        names.push(v);
+    }
+
+    let names_ref = doc.catalog()?.get(b"Names").ok().cloned();
+    let mut names_dict = match &names_ref {
+        Some(o) => doc.dereference(o)?.1.as_dict()?.clone(),
+        None => Dictionary::new(),
+    };
+    let root = names_dict.get(b"Dests").and_then(Object::as_reference).ok();
+    for id in dest_tree_objects(doc) {
+        doc.objects.remove(&id);
+    }
+    let tree = Object::Dictionary(dictionary! { "Names" => names });
+    let tree_id = match root {
+        Some(id) => {
+            doc.objects.insert(id, tree);
+            id
+        }
+        None => doc.add_object(tree),
+    };
+    names_dict.set("Dests", tree_id);
+
+    match names_ref {
+        Some(Object::Reference(id)) => {
+            doc.objects.insert(id, Object::Dictionary(names_dict));
+        }
+        _ => doc.catalog_mut()?.set("Names", Object::Dictionary(names_dict)),
+    }
+    doc.catalog_mut()?.remove(b"Dests");
+    Ok(())
+}
+
+/// `add`를 `base`에 합치기 전 (`add` 재번호 이후) 호출:
+/// `add`의 이름 있는 목적지를 `base`의 이름 트리로 옮기고, 이름이 겹치면 `이름-2`, `이름-3` … 으로 바꾼 뒤
+/// `add` 쪽 링크/책갈피의 이름 참조도 같이 바꾼다. 페이지 참조 목적지는 재번호로 이미 맞춰져 있다
+pub fn merge_named_dests(base: &mut Document, add: &mut Document) -> Result<(), Box<dyn Error>> {
+    let add_entries = named_dests(add);
+    if add_entries.is_empty() {
+        return Ok(());
+    }
+    let base_entries = named_dests(base);
+    // base가 옛 /Dests(이름 키)를 쓰면 참조를 문자열 형식으로 통일
+    if base.catalog()?.has(b"Dests") {
+        rewrite_named_refs(base, &HashMap::new());
+    }
+
+    let mut taken: HashSet<Vec<u8>> = base_entries.iter().map(|(k, _)| k.clone()).collect();
+    let mut rename = HashMap::new();
+    let mut entries = base_entries;
+    for (name, dest) in add_entries {
+        let mut new = name.clone();
+        let mut k = 2;
+        while taken.contains(&new) {
+            new = [name.as_slice(), format!("-{k}").as_bytes()].concat();
+            k += 1;
+        }
+        taken.insert(new.clone());
+        if new != name {
+            rename.insert(name, new.clone());
+        }
+        entries.push((new, dest));
+    }
+    rewrite_named_refs(add, &rename);
+    // 목적지는 base 트리로 옮겼으니 add의 트리 노드는 같이 합쳐지지 않도록 지움
+    for id in dest_tree_objects(add) {
+        add.objects.remove(&id);
+    }
+    write_name_tree(base, entries)
+}
+
+/// 페이지 삭제 후: 사라진 페이지를 가리키는 이름 있는 목적지와 링크 주석 제거
+pub fn prune_dangling(doc: &mut Document) -> Result<(), Box<dyn Error>> {
+    let pages: HashSet<ObjectId> = doc.get_pages().values().copied().collect();
+
+    let entries = named_dests(doc);
+    if !entries.is_empty() {
+        let alive: Vec<(Vec<u8>, Object)> = entries.iter()
+            .filter(|(_, d)| {
+                let d = match doc.dereference(d) {
+                    Ok((_, Object::Dictionary(d))) => d.get(b"D").ok(),
+                    Ok((_, o)) => Some(o),
+                    Err(_) => None,
+                };
+                d.and_then(|d| dest_page(doc, d)).is_some_and(|p| pages.contains(&p))
+            })
+            .cloned()
+            .collect();
+        if alive.len() != entries.len() {
+            write_name_tree(doc, alive)?;
+        }
+    }
+
+    for pid in &pages {
+        let Ok(page) = doc.get_object(*pid).and_then(|o| o.as_dict()) else { continue };
+        let Ok((_, Object::Array(annots))) = page.get(b"Annots").and_then(|o| doc.dereference(o)) else { continue };
+        let kept: Vec<Object> = annots.iter()
+            .filter(|a| {
+                let Ok((_, Object::Dictionary(annot))) = doc.dereference(a) else { return true };
+                let is_internal = annot.has(b"Dest")
+                    || doc.dereference(annot.get(b"A").unwrap_or(&Object::Null)).ok()
+                        .and_then(|(_, a)| a.as_dict().ok())
+                        .is_some_and(|a| a.get(b"S").and_then(|s| s.as_name()).ok() == Some(b"GoTo".as_slice()));
+                !is_internal || target_page(doc, annot).is_some_and(|p| pages.contains(&p))
+            })
+            .cloned()
+            .collect();
+        if kept.len() != annots.len() {
+            let pd = doc.get_object_mut(*pid)?.as_dict_mut()?;
+            if kept.is_empty() {
+                pd.remove(b"Annots");
+            } else {
+                pd.set("Annots", Object::Array(kept));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 행렬 `m`을 적용한 사각형의 네 모서리를 감싸는 사각형
+fn transform_rect(m: &[f64; 6], [x0, y0, x1, y1]: [f64; 4]) -> [f64; 4] {
+    let point = |x: f64, y: f64| (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5]);
+    let corners = [point(x0, y0), point(x1, y0), point(x0, y1), point(x1, y1)];
+    let xs = corners.iter().map(|c| c.0);
+    let ys = corners.iter().map(|c| c.1);
+    [
+        xs.clone().fold(f64::INFINITY, f64::min),
+        ys.clone().fold(f64::INFINITY, f64::min),
+        xs.fold(f64::NEG_INFINITY, f64::max),
+        ys.fold(f64::NEG_INFINITY, f64::max),
+    ]
+}
+
+/// `page_id`를 가리키는 목적지 배열의 좌표를 `m`으로 옮긴다:
+/// /XYZ left top, /FitH·/FitBH top, /FitV·/FitBV left, /FitR 사각형. null(현재 보기 유지)은 그대로
+fn transform_dest(dest: &mut [Object], page_id: ObjectId, m: &[f64; 6]) {
+    if dest.first().and_then(|p| p.as_reference().ok()) != Some(page_id) {
+        return;
+    }
+    let Some(kind) = dest.get(1).and_then(|k| k.as_name().ok()).map(<[u8]>::to_vec) else { return };
+    let num = |dest: &[Object], i: usize| dest.get(i).and_then(crate::process_pages::as_f64);
+    let real = |v: f64| Object::Real(v as f32);
+    match kind.as_slice() {
+        b"XYZ" => match (num(dest, 2), num(dest, 3)) {
+            (Some(x), Some(y)) => {
+                dest[2] = real(m[0] * x + m[2] * y + m[4]);
+                dest[3] = real(m[1] * x + m[3] * y + m[5]);
+            }
+            (Some(x), None) => dest[2] = real(m[0] * x + m[4]),
+            (None, Some(y)) => dest[3] = real(m[3] * y + m[5]),
+            (None, None) => {}
+        },
+        b"FitH" | b"FitBH" => {
+            if let Some(y) = num(dest, 2) {
+                dest[2] = real(m[3] * y + m[5]);
+            }
+        }
+        b"FitV" | b"FitBV" => {
+            if let Some(x) = num(dest, 2) {
+                dest[2] = real(m[0] * x + m[4]);
+            }
+        }
+        b"FitR" => {
+            if let (Some(x0), Some(y0), Some(x1), Some(y1)) = (num(dest, 2), num(dest, 3), num(dest, 4), num(dest, 5)) {
+                for (i, v) in transform_rect(m, [x0, y0, x1, y1]).into_iter().enumerate() {
+                    dest[2 + i] = real(v);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 목적지가 들어 있을 수 있는 키: 링크/책갈피(/Dest, /A → /D), 이름 값(/D), 이름 트리(/Names, /Kids),
+/// 카탈로그(/Names → /Dests, 옛 /Dests)
+const DEST_KEYS: [&[u8]; 6] = [b"Dest", b"A", b"D", b"Names", b"Kids", b"Dests"];
+
+/// `obj` 안의 목적지 배열을 변환하고, 따라가야 할 간접 참조는 (`all_values`와 함께) `refs`에 모은다.
+/// `all_values`: 옛 /Dests처럼 모든 값이 목적지인 딕셔너리
+fn transform_dests_in(obj: &mut Object, page_id: ObjectId, m: &[f64; 6], refs: &mut Vec<(ObjectId, bool)>, all_values: bool) {
+    match obj {
+        Object::Reference(id) => refs.push((*id, all_values)),
+        Object::Array(a) => {
+            let is_dest = matches!(a.as_slice(), [Object::Reference(_), Object::Name(_), ..]);
+            if is_dest {
+                transform_dest(a, page_id, m);
+            } else {
+                for o in a.iter_mut() {
+                    transform_dests_in(o, page_id, m, refs, false);
+                }
+            }
+        }
+        Object::Dictionary(d) => {
+            for (k, v) in d.iter_mut() {
+                if all_values || DEST_KEYS.contains(&k.as_slice()) {
+                    transform_dests_in(v, page_id, m, refs, k.as_slice() == b"Dests");
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 페이지 내용에 `m`을 적용했을 때 그 페이지를 가리키는 목적지(링크, 책갈피, 이름 있는 목적지)도 같이 옮긴다
+fn transform_dests(doc: &mut Document, page_id: ObjectId, m: &[f64; 6]) {
+    let mut queue: Vec<(ObjectId, bool)> = annotation_ids(doc).into_iter()
+        .chain(outline::all_items(doc))
+        .chain(doc.trailer.get(b"Root").and_then(Object::as_reference).ok())
+        .map(|id| (id, false))
+        .collect();
+    let mut seen = HashSet::new();
+    while let Some((id, all_values)) = queue.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        if let Ok(obj) = doc.get_object_mut(id) {
+            transform_dests_in(obj, page_id, m, &mut queue, all_values);
+        }
+    }
+}
+
+/// 페이지 내용을 `s`배 + (tx, ty) 이동했을 때 그 페이지 주석의 /Rect, /QuadPoints도 같이 옮긴다
+pub fn transform_annotations(doc: &mut Document, page_id: ObjectId, s: f64, tx: f64, ty: f64) -> Result<(), Box<dyn Error>> {
+    transform_annotations_by(doc, page_id, [s, 0.0, 0.0, s, tx, ty])
+}
+
+/// 페이지 내용에 행렬 `m` (PDF `cm` 순서 a b c d e f)을 적용했을 때 주석 좌표와 이 페이지로 가는 목적지 변환.
+/// 회전이 섞이면 /Rect는 변환된 네 모서리를 감싸는 사각형이 된다
+pub fn transform_annotations_by(doc: &mut Document, page_id: ObjectId, m: [f64; 6]) -> Result<(), Box<dyn Error>> {
+    let annots: Vec<ObjectId> = match doc.get_object(page_id)?.as_dict()?.get(b"Annots") {
+        Ok(o) => match doc.dereference(o)?.1 {
+            Object::Array(a) => a.iter().filter_map(|a| a.as_reference().ok()).collect(),
+            _ => Vec::new(),
+        },
+        Err(_) => Vec::new(),
+    };
+    let point = |x: f64, y: f64| (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5]);
+    let numbers = |a: &[Object]| -> Option<Vec<f64>> { a.iter().map(crate::process_pages::as_f64).collect() };
+    for id in annots {
+        let Ok(annot) = doc.get_object_mut(id).and_then(|o| o.as_dict_mut()) else { continue };
+        if let Ok(Object::Array(a)) = annot.get(b"Rect")
+            && let Some([x0, y0, x1, y1]) = numbers(a).and_then(|v| <[f64; 4]>::try_from(v).ok())
+        {
+            let rect = transform_rect(&m, [x0, y0, x1, y1]);
+            annot.set("Rect", Object::Array(rect.iter().map(|v| Object::Real(*v as f32)).collect()));
+        }
+        if let Ok(Object::Array(a)) = annot.get(b"QuadPoints")
+            && let Some(v) = numbers(a)
+        {
+            let moved = v.chunks_exact(2)
+                .flat_map(|p| {
+                    let (x, y) = point(p[0], p[1]);
+                    [Object::Real(x as f32), Object::Real(y as f32)]
+                })
+                .collect();
+            annot.set("QuadPoints", Object::Array(moved));
+        }
+    }
+    transform_dests(doc, page_id, &m);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page_tree;
+
+    /// 한 쪽짜리 문서: 이름 트리에 `name` → 그 페이지, 페이지에는 그 이름으로 가는 GoTo 링크
+    fn doc_with_dest(name: &str) -> (Document, ObjectId, ObjectId) {
+        let mut doc = Document::with_version("1.7");
+        let root = doc.new_object_id();
+        let pid = doc.new_object_id();
+        let link = doc.add_object(dictionary! {
+            "Type" => "Annot", "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            "A" => dictionary! { "S" => "GoTo", "D" => Object::string_literal(name) },
+        });
+        doc.objects.insert(pid, Object::Dictionary(dictionary! {
+            "Type" => "Page",
+            "MediaBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+            "Annots" => vec![link.into()],
+        }));
+        page_tree::build_balanced(&mut doc, root, &[pid]).unwrap();
+        let tree = doc.add_object(dictionary! {
+            "Names" => vec![Object::string_literal(name), vec![pid.into(), "Fit".into()].into()],
+        });
+        let catalog = doc.add_object(dictionary! {
+            "Type" => "Catalog", "Pages" => root, "Names" => dictionary! { "Dests" => tree },
+        });
+        doc.trailer.set("Root", catalog);
+        (doc, pid, link)
+    }
+
+    fn names(doc: &Document) -> Vec<String> {
+        named_dests(doc).iter().map(|(k, _)| String::from_utf8_lossy(k).into_owned()).collect()
+    }
+
+    fn tree_root(doc: &Document) -> ObjectId {
+        let names = doc.catalog().unwrap().get(b"Names").unwrap().as_dict().unwrap();
+        names.get(b"Dests").unwrap().as_reference().unwrap()
+    }
+
+    fn link_target(doc: &Document, link: ObjectId) -> Object {
+        let annot = doc.get_object(link).unwrap().as_dict().unwrap();
+        annot.get(b"A").unwrap().as_dict().unwrap().get(b"D").unwrap().clone()
+    }
+
+    #[test]
+    fn merge_renames_clashing_dests() {
+        let (mut base, base_page, base_link) = doc_with_dest("toc");
+        let base_tree = tree_root(&base);
+        let (mut add, _, _) = doc_with_dest("toc");
+        add.renumber_objects_with(base.max_id + 1);
+        let add_tree = tree_root(&add);
+        // 재번호로 바뀐 링크 번호
+        let add_link = annotation_ids(&add)[0];
+
+        merge_named_dests(&mut base, &mut add).unwrap();
+
+        assert_eq!(names(&base), ["toc", "toc-2"]);
+        assert_eq!(dest_page(&base, &Object::string_literal("toc")), Some(base_page));
+        // add 쪽 링크만 새 이름으로
+        assert_eq!(link_target(&add, add_link), Object::string_literal("toc-2"));
+        assert_eq!(link_target(&base, base_link), Object::string_literal("toc"));
+        // base 트리는 같은 객체에 다시 쓰고, add 트리 노드는 합쳐지지 않도록 제거
+        assert_eq!(tree_root(&base), base_tree);
+        assert!(!add.objects.contains_key(&add_tree));
+    }
+
+    #[test]
+    fn repeated_merges_keep_one_tree() {
+        let (mut base, _, _) = doc_with_dest("ch");
+        let before = base.objects.len();
+        for _ in 0..3 {
+            let (mut add, _, _) = doc_with_dest("ch");
+            add.renumber_objects_with(base.max_id + 1);
+            merge_named_dests(&mut base, &mut add).unwrap();
+        }
+        assert_eq!(names(&base), ["ch", "ch-2", "ch-3", "ch-4"]);
+        assert_eq!(base.objects.len(), before);
+    }
+
+    #[test]
+    fn old_style_dests_move_into_the_name_tree() {
+        let (mut base, pid, link) = doc_with_dest("a");
+        // 옛 /Dests 딕셔너리 + 이름 객체로 가는 링크
+        let old = base.add_object(dictionary! { "b" => vec![pid.into(), "Fit".into()] });
+        base.catalog_mut().unwrap().set("Dests", old);
+        base.get_object_mut(link).unwrap().as_dict_mut().unwrap()
+            .set("A", dictionary! { "S" => "GoTo", "D" => Object::Name(b"b".to_vec()) });
+        let (mut add, _, _) = doc_with_dest("b");
+        add.renumber_objects_with(base.max_id + 1);
+
+        merge_named_dests(&mut base, &mut add).unwrap();
+
+        assert!(!base.catalog().unwrap().has(b"Dests"));
+        assert!(!base.objects.contains_key(&old));
+        assert_eq!(names(&base), ["a", "b", "b-2"]);
+        assert_eq!(link_target(&base, link), Object::string_literal("b"));
+    }
+
+    #[test]
+    fn destinations_follow_page_transform() {
+        let (mut doc, pid, link) = doc_with_dest("top");
+        let dest = |kind: &str, args: Vec<Object>| -> Object {
+            [vec![pid.into(), kind.into()], args].concat().into()
+        };
+        // 링크의 /Dest, 다른 링크의 /A /D, 이름 트리 값이 모두 페이지 내용을 따라 움직여야 함
+        doc.get_object_mut(link).unwrap().as_dict_mut().unwrap()
+            .set("Dest", dest("XYZ", vec![10.into(), 90.into(), Object::Null]));
+        let fith = doc.add_object(dictionary! {
+            "Type" => "Annot", "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 1.into(), 1.into()],
+            "A" => dictionary! { "S" => "GoTo", "D" => dest("FitH", vec![50.into()]) },
+        });
+        doc.get_object_mut(pid).unwrap().as_dict_mut().unwrap()
+            .get_mut(b"Annots").unwrap().as_array_mut().unwrap().push(fith.into());
+        let tree = tree_root(&doc);
+        doc.get_object_mut(tree).unwrap().as_dict_mut().unwrap()
+            .set("Names", vec![Object::string_literal("left"), dest("FitV", vec![20.into()])]);
+
+        transform_annotations(&mut doc, pid, 0.5, 5.0, 7.0).unwrap();
+
+        let numbers = |o: &Object| -> Vec<Option<f64>> {
+            o.as_array().unwrap()[2..].iter().map(crate::process_pages::as_f64).collect()
+        };
+        let annot = doc.get_object(link).unwrap().as_dict().unwrap();
+        assert_eq!(numbers(annot.get(b"Dest").unwrap()), [Some(10.0), Some(52.0), None]);
+        let rect: Vec<f64> = annot.get(b"Rect").unwrap().as_array().unwrap().iter()
+            .filter_map(crate::process_pages::as_f64).collect();
+        assert_eq!(rect, [5.0, 7.0, 10.0, 12.0]);
+        let action = doc.get_object(fith).unwrap().as_dict().unwrap().get(b"A").unwrap().as_dict().unwrap();
+        assert_eq!(numbers(action.get(b"D").unwrap()), [Some(32.0)]);
+        let (_, left) = &named_dests(&doc)[0];
+        assert_eq!(numbers(left), [Some(15.0)]);
+    }
+}
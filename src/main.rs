@@ -1,18 +1,41 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use glob::glob;
 use lopdf::{Document, Object, ObjectId, Stream};
 use lopdf::dictionary;
+use lopdf::content::{Content as LoContent, Operation};
 use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref as PdfRef};
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 mod process_pages;
 mod binding_params;
-use binding_params::{UnitSystem, BookParams, Book, BINDING_PARAMS_KDP_WHITE};
+mod config;
+mod page_meta;
+mod ink;
+mod image_ink;
+mod stamp;
+mod outline;
+mod links;
+mod page_tree;
+mod assemble;
+mod normalize;
+mod cover;
+use binding_params::{BindingDirection, UnitSystem, BookBindingConstant, BookParams, Book, Paper};
+use config::{BlankConfig, BlankPolicy, BlankTemplate, FitPolicyConfig, MatterResize, PageConfig, ProjectConfig, SectionStart, SvgOptions};
+use assemble::Assembly;
+use page_meta::{PageKind, PageMeta};
+use process_pages::{fit_with_anchor, helvetica_text_width, Anchor, AxisAnchor, FitMode};
+
+/// Output to build (both by default)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Output {
+    /// ./book.pdf (print interior)
+    Book,
+    /// ./book_ARC.pdf (advance reader copy)
+    Arc,
+}
 
-/// Bind front + SVGs + back into a single PDF (vector)
-#[derive(Parser, Debug)]
-#[command(name="pdf_bind", about="Bind front + SVGs + back into a single PDF")]
+#[derive(Parser, Debug, Clone)]
+#[command(name="pdf_bind", about="Bind front + SVGs + back into ./book.pdf and ./book_ARC.pdf")]
 struct Args {
     /// Target page width (default: 8.5)
     #[arg(long, default_value_t = 8.5)]
@@ -26,38 +49,40 @@ struct Args {
     /// Number of pages (default: 1)
     #[arg(long, default_value_t = 50)]
     num_pages: i64,
+    /// Interior paper stock, used for the spine width (default: white)
+    #[arg(long, value_enum, default_value_t = Paper::White)]
+    paper: Paper,
     /// If true, and front_matter page count is odd, insert a blank page to make it even
     #[arg(long, default_value_t = false)]
     make_even: bool,
-    /// ARC mode: true => DO NOT insert blanks between SVG pages; false => insert 1 blank between SVG pages
-    #[arg(long, default_value_t = false)]
+    /// Build only this output instead of both
+    #[arg(long, value_enum)]
+    only: Option<Output>,
+    /// ARC mode: drop blank pages and stamp watermarks; blank policy defaults to `none` instead of `single-sided`.
+    /// Set per output by `main`
+    #[arg(skip)]
     arc: bool,
-}
-
-impl Args {
-    /// Book preset: 8.5x8.5 inches, make_even=false, arc=false
-    pub fn book() -> Args {
-        Args {
-            width: 8.5,
-            height: 8.5,
-            unit_system: UnitSystem::Inch,
-            num_pages: 50,
-            make_even: false,
-            arc: false,
-        }
-    }
-
-    /// ARC preset: 8.5x8.5 inches, make_even=false, arc=true
-    pub fn arc() -> Args {
-        Args {
-            width: 8.5,
-            height: 8.5,
-            unit_system: UnitSystem::Inch,
-            num_pages: 50,
-            make_even: false,
-            arc: true,
-        }
-    }
+    /// ARC mode: list the pages blank removal would drop, but keep them in the output
+    #[arg(long, default_value_t = false)]
+    blank_dry_run: bool,
+    /// Blank page policy (overrides `[blanks] policy` in the config)
+    #[arg(long, value_enum)]
+    blank_policy: Option<BlankPolicy>,
+    /// Gutter loss compensation for `*.spread.svg` pages, in `unit_system` units (default: 0)
+    #[arg(long, default_value_t = 0.0)]
+    spread_gutter_comp: f64,
+    /// Project config (svg2pdf options, per-page entries); missing file => defaults
+    #[arg(long, default_value = "./materials/book.toml")]
+    config: PathBuf,
+    /// Sparse-page threshold for inner-margin fitting (overrides `[fit.default]` and every `[fit.<section>]`)
+    #[arg(long)]
+    sparse_threshold: Option<f64>,
+    /// Fit mode for inner-margin fitting (overrides `[fit.default]` and every `[fit.<section>]`)
+    #[arg(long, value_enum)]
+    fit_mode: Option<FitMode>,
+    /// Binding direction: ltr (default) or rtl (overrides `direction` in the config)
+    #[arg(long, value_enum)]
+    direction: Option<BindingDirection>,
 }
 
 fn to_points(v: f64, unit: &str) -> f64 {
@@ -68,52 +93,29 @@ fn to_points(v: f64, unit: &str) -> f64 {
     }
 }
 
-/// xref 안정화를 위해 입력 PDF를 로드 후 곧바로 저장
-fn roundtrip_save(input: &Path, out: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let mut doc = Document::load(input)?;
-    doc.save(out)?;
-    Ok(())
-}
-
-fn pages_root_id(doc: &Document) -> Result<ObjectId, Box<dyn std::error::Error>> {
-    let catalog = doc.catalog()?;
-    let pages_ref = catalog.get(b"Pages")?.as_reference()?;
-    Ok(pages_ref)
-}
-
-/// base 뒤에 add 문서의 모든 페이지를 append (Parent 교체 + Kids/Count 갱신)
-fn append_doc(mut base: Document, mut add: Document) -> Result<Document, Box<dyn std::error::Error>> {
-    let base_pages_id = pages_root_id(&base)?;
-    let base_page_count = base.get_pages().len() as i64;
-
-    add.renumber_objects_with(base.max_id + 1);
-
-    let add_page_ids: Vec<ObjectId> = add.get_pages().values().cloned().collect();
-    for pid in &add_page_ids {
-        let obj = add.get_object_mut(*pid)?;
-        let dict = obj.as_dict_mut()?;
-        dict.set("Parent", base_pages_id);
-    }
-
-    base.objects.extend(add.objects);
-
-    {
-        let pages_obj = base.get_object_mut(base_pages_id)?;
-        let pages_dict = pages_obj.as_dict_mut()?;
-        let kids_obj = pages_dict.get_mut(b"Kids")?;
-        let kids = kids_obj.as_array_mut()?;
-        for pid in &add_page_ids {
-            kids.push(Object::Reference(*pid));
-        }
-        let new_count = base_page_count + add_page_ids.len() as i64;
-        pages_dict.set("Count", Object::Integer(new_count as i64));
+/// 카탈로그에 /ViewerPreferences /Direction /R2L 기록 (우철 제본)
+fn set_viewer_direction_r2l(doc: &mut Document) -> Result<(), Box<dyn std::error::Error>> {
+    let prefs_ref = match doc.catalog()?.get(b"ViewerPreferences") {
+        Ok(Object::Reference(id)) => Some(*id),
+        _ => None,
+    };
+    if let Some(id) = prefs_ref {
+        doc.get_object_mut(id)?.as_dict_mut()?.set("Direction", "R2L");
+        return Ok(());
     }
 
-    base.renumber_objects();
-    Ok(base)
+    let catalog = doc.catalog_mut()?;
+    let mut prefs = match catalog.get(b"ViewerPreferences") {
+        Ok(Object::Dictionary(d)) => d.clone(),
+        _ => lopdf::Dictionary::new(),
+    };
+    prefs.set("Direction", "R2L");
+    catalog.set("ViewerPreferences", Object::Dictionary(prefs));
+    Ok(())
 }
 
-/// 모든 페이지의 MediaBox/CropBox를 지정 크기로 통일
+/// 모든 페이지의 MediaBox/CropBox/TrimBox/BleedBox를 지정 크기로 통일.
+/// 입력의 ArtBox는 더 이상 맞지 않으므로 제거 (책 출력은 `set_print_boxes`가 새로 기록)
 fn enforce_page_size(doc: &mut Document, w_pt: f64, h_pt: f64) -> Result<(), Box<dyn std::error::Error>> {
     let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
     let box_obj = Object::Array(vec![0.0.into(), 0.0.into(), w_pt.into(), h_pt.into()]);
@@ -122,19 +124,49 @@ fn enforce_page_size(doc: &mut Document, w_pt: f64, h_pt: f64) -> Result<(), Box
         let dict = obj.as_dict_mut()?;
         dict.set("MediaBox", box_obj.clone());
         dict.set("CropBox",  box_obj.clone());
+        dict.set("TrimBox",  box_obj.clone());
+        dict.set("BleedBox", box_obj.clone());
+        dict.remove(b"ArtBox");
     }
     Ok(())
 }
 
-/// 지정 크기의 "빈 페이지 1장"만 가진 PDF 문서 생성
-fn blank_page_doc(w_pt: f64, h_pt: f64) -> Document {
+/// ARC 빈 페이지 제거 보고 (제거 전 쪽 위치 / 출처 / 이유)
+fn print_blank_report(removed: &[process_pages::RemovedPage], dry_run: bool) {
+    let heading = if dry_run { "Blank pages that would be removed (dry run)" } else { "Blank pages removed" };
+    println!("{heading}: {}", removed.len());
+    for r in removed {
+        let source = if r.source.is_empty() { "-" } else { r.source.as_str() };
+        println!("  p.{:<4} {:<32} [{}] {}", r.index, source, r.section, r.reason);
+    }
+}
+
+/// 판형과 크기가 다른 front/back matter 페이지 경고
+fn warn_mismatched(file: &str, pages: &[process_pages::MismatchedPage], w_pt: f64, h_pt: f64, mode: MatterResize) {
+    if pages.is_empty() {
+        return;
+    }
+    let action = match mode {
+        MatterResize::Crop => "cropped to the trim size; set [matter] resize to scale them",
+        MatterResize::Fit => "scaled to fit the trim size",
+        MatterResize::Fill => "scaled to fill the trim size",
+        MatterResize::Center => "centered on the trim size",
+    };
+    eprintln!("Warning: {file}: {} page(s) are not {w_pt:.1} x {h_pt:.1} pt and were {action}:", pages.len());
+    for p in pages {
+        eprintln!("  p.{:<4} {:.1} x {:.1} pt", p.index, p.width, p.height);
+    }
+}
+
+/// 지정 크기의 페이지 1장(`content` + `resources`)만 가진 PDF 문서 생성
+fn single_page_doc(w_pt: f64, h_pt: f64, content: Vec<u8>, resources: lopdf::Dictionary) -> Document {
     let mut doc = Document::with_version("1.5");
     let pages_id = doc.new_object_id();
     let page_id = doc.new_object_id();
     let contents_id = doc.new_object_id();
     let catalog_id = doc.new_object_id();
 
-    let stream = Stream::new(lopdf::Dictionary::new(), Vec::<u8>::new());
+    let stream = Stream::new(lopdf::Dictionary::new(), content);
     doc.objects.insert(contents_id, Object::Stream(stream));
 
     let page_dict = dictionary! {
@@ -142,7 +174,7 @@ fn blank_page_doc(w_pt: f64, h_pt: f64) -> Document {
         "Parent" => pages_id,
         "MediaBox" => Object::Array(vec![0.0.into(), 0.0.into(), w_pt.into(), h_pt.into()]),
         "CropBox"  => Object::Array(vec![0.0.into(), 0.0.into(), w_pt.into(), h_pt.into()]),
-        "Resources" => lopdf::Dictionary::new(),
+        "Resources" => resources,
         "Contents" => contents_id,
     };
     doc.objects.insert(page_id, Object::Dictionary(page_dict));
@@ -163,28 +195,159 @@ fn blank_page_doc(w_pt: f64, h_pt: f64) -> Document {
     doc
 }
 
-/// SVG → (벡터) **한 장짜리 페이지 PDF** 바이트 생성 (메모리)
-///  - 페이지 크기: w_pt x h_pt
-///  - 배치: **비율 유지(contain) + 중앙정렬**
-fn svg_to_page_pdf_bytes(svg_path: &Path, w_pt: f64, h_pt: f64) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    // 1) SVG 파싱
-    let svg_str = std::fs::read_to_string(svg_path)?;
+/// 가운데 정렬된 한 줄 안내문 (Helvetica, WinAnsi로 옮길 수 없는 문자는 `?`로 바꾸고 경고)
+fn notice_content(w_pt: f64, h_pt: f64, text: &str) -> Vec<u8> {
+    let size = 11.0;
+    let mut missing = BTreeSet::new();
+    let text = stamp::to_win_ansi(text, &mut missing);
+    stamp::warn_missing("[blanks] notice_text", &missing);
+    let text_w = helvetica_text_width(&text, size);
+    format!(
+        "q\n0.45 g\nBT\n/F1 {size:.3} Tf\n{x:.3} {y:.3} Td\n({text}) Tj\nET\nQ\n",
+        x = (w_pt - text_w) / 2.0, y = (h_pt - size) / 2.0, text = stamp::escape_pdf_text(&text),
+    )
+    .into_bytes()
+}
+
+/// 마커 테스트/비침 확인용 정사각형 격자 (4 × 5, 페이지 중앙)
+fn swatch_grid_content(w_pt: f64, h_pt: f64) -> Vec<u8> {
+    let (cols, rows) = (4, 5);
+    let cell = (w_pt * 0.7 / cols as f64).min(h_pt * 0.7 / rows as f64);
+    let x0 = (w_pt - cell * cols as f64) / 2.0;
+    let y0 = (h_pt - cell * rows as f64) / 2.0;
+    let inset = cell * 0.1;
+
+    let mut ops = vec![
+        Operation::new("q", vec![]),
+        Operation::new("G", vec![0.6.into()]),
+        Operation::new("w", vec![0.75.into()]),
+    ];
+    for r in 0..rows {
+        for c in 0..cols {
+            let x = x0 + c as f64 * cell + inset;
+            let y = y0 + r as f64 * cell + inset;
+            let side = cell - 2.0 * inset;
+            ops.push(Operation::new("re", vec![x.into(), y.into(), side.into(), side.into()]));
+        }
+    }
+    ops.push(Operation::new("S", vec![]));
+    ops.push(Operation::new("Q", vec![]));
+    LoContent { operations: ops }.encode().unwrap_or_default()
+}
+
+/// 생성되는 빈 페이지(뒷면) 템플릿. `[blanks] template`에 따라 페이지 1장짜리 문서를 한 번 만들어 두고
+/// 조립 시 모든 빈 페이지가 콘텐츠/리소스를 공유한다.
+/// 어떤 템플릿이든 Blank로 태깅되므로 ARC의 `remove_blank_pages`가 내용과 상관없이 제거한다
+struct BackingPages {
+    template: Document,
+}
+
+impl BackingPages {
+    fn new(
+        cfg: &BlankConfig,
+        w_pt: f64,
+        h_pt: f64,
+        svg_opts: &SvgOptions,
+    ) -> Result<BackingPages, Box<dyn std::error::Error>> {
+        let template = match cfg.template {
+            BlankTemplate::Empty => single_page_doc(w_pt, h_pt, Vec::new(), lopdf::Dictionary::new()),
+            BlankTemplate::Notice => {
+                let text = cfg.notice_text.as_deref().unwrap_or("This page intentionally left blank.");
+                let resources = dictionary! {
+                    "Font" => dictionary! {
+                        "F1" => dictionary! {
+                            "Type" => "Font",
+                            "Subtype" => "Type1",
+                            "BaseFont" => "Helvetica",
+                            "Encoding" => "WinAnsiEncoding",
+                        },
+                    },
+                };
+                single_page_doc(w_pt, h_pt, notice_content(w_pt, h_pt, text), resources)
+            }
+            BlankTemplate::Swatches => {
+                single_page_doc(w_pt, h_pt, swatch_grid_content(w_pt, h_pt), lopdf::Dictionary::new())
+            }
+            BlankTemplate::File => {
+                let path = cfg.template_file.as_deref()
+                    .ok_or("[blanks] template = \"file\" requires template_file")?;
+                let is_svg = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+                let mut doc = if is_svg {
+                    let placement = SvgPlacement {
+                        fit: FitMode::Contain,
+                        anchor: Anchor { x: AxisAnchor::Center, y: AxisAnchor::Center },
+                        bleed_pt: 0.0,
+                        fit_to_ink: false,
+                    };
+                    Document::load_mem(&svg_to_page_pdf_bytes(path, w_pt, h_pt, &placement, svg_opts)?)?
+                } else {
+                    // 첫 페이지만 사용
+                    let mut doc = normalize::load_normalized(path)?;
+                    let extra: Vec<ObjectId> = doc.get_pages().values().skip(1).cloned().collect();
+                    if !extra.is_empty() {
+                        for pid in extra {
+                            page_tree::delete_page(&mut doc, pid)?;
+                        }
+                        doc.prune_objects();
+                    }
+                    enforce_page_size(&mut doc, w_pt, h_pt)?;
+                    doc
+                };
+                if doc.get_pages().is_empty() {
+                    return Err(format!("{}: no pages", path.display()).into());
+                }
+                doc.renumber_objects();
+                doc
+            }
+        };
+        Ok(BackingPages { template })
+    }
+}
+
+/// 다음 페이지가 섹션 시작 규칙(recto=홀수 / verso=짝수)을 만족하도록 필요하면 빈 페이지 1장 추가.
+/// 추가되는 빈 페이지는 직전 섹션(`prev_section`)의 끝으로 취급
+fn pad_section_start(asm: &mut Assembly, start: SectionStart, prev_section: &str) {
+    let next_page = asm.page_count() + 1;
+    let needs_blank = match start {
+        SectionStart::Recto => next_page.is_multiple_of(2),
+        SectionStart::Verso => !next_page.is_multiple_of(2),
+        SectionStart::Any => false,
+    };
+    if needs_blank {
+        asm.push_blank(prev_section);
+    }
+}
+
+/// SVG 소스 파싱 (시스템 폰트 로드 포함)
+fn parse_svg_tree(svg_str: &str) -> Result<svg2pdf::usvg::Tree, Box<dyn std::error::Error>> {
     let mut opt = svg2pdf::usvg::Options::default();
     opt.fontdb_mut().load_system_fonts();
-    let tree = svg2pdf::usvg::Tree::from_str(&svg_str, &opt)?;
+    let tree = svg2pdf::usvg::Tree::from_str(svg_str, &opt)?;
+    Ok(tree)
+}
 
-    // 2) SVG → PDF Chunk(XObject) 변환
+/// usvg 트리 → (벡터) **한 장짜리 페이지 PDF** 바이트 생성 (메모리)
+///  - 페이지 크기: w_pt x h_pt
+///  - 배치: `transform` (XObject는 1 × 1 단위 정사각형이므로 a, d가 곧 그려질 폭/높이)
+fn svg_tree_to_page_pdf_bytes(
+    tree: &svg2pdf::usvg::Tree,
+    w_pt: f64,
+    h_pt: f64,
+    transform: [f32; 6],
+    opts: &SvgOptions,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    // 1) SVG → PDF Chunk(XObject) 변환
     let (svg_chunk, svg_root_ref) = svg2pdf::to_chunk(
-        &tree,
-        svg2pdf::ConversionOptions::default(),
+        tree,
+        opts.to_conversion_options(),
     )
     .map_err(|e| {
-        let err = std::io::Error::new(std::io::ErrorKind::Other, format!("svg2pdf to_chunk failed: {e}"));
+        let err = std::io::Error::other(format!("svg2pdf to_chunk failed: {e}"));
         Box::<dyn std::error::Error>::from(err)
     })?; // returns (Chunk, Ref)
     // 참고: 공식 예제에서 이 Chunk를 페이지에 임베드하고 transform 행렬로 배치함. :contentReference[oaicite:2]{index=2}
 
-    // 3) pdf-writer로 단일 페이지 구성 + transform 계산
+    // 2) pdf-writer로 단일 페이지 구성
     let mut alloc = PdfRef::new(1);
     let catalog_id   = alloc.bump();
     let page_tree_id = alloc.bump();
@@ -214,17 +377,9 @@ fn svg_to_page_pdf_bytes(svg_path: &Path, w_pt: f64, h_pt: f64) -> Result<Vec<u8
     res.finish();
     page.finish();
 
-    // ===== 변환 행렬 계산 (contain + center) =====
-    // XObject는 1pt × 1pt 단위를 기본으로 삼으므로,
-    // 균등 스케일 s를 선택하고 가운데로 이동(tx, ty)시킵니다.
-    // (이 방식은 SVG의 종횡비를 유지하고, 페이지 내에 letterbox가 생길 수 있음)
-    let s = w_pt.min(h_pt);            // 사용 가능한 폭/높이 중 작은 값
-    let tx = (w_pt - s) / 2.0;         // 중앙 정렬 X
-    let ty = (h_pt - s) / 2.0;         // 중앙 정렬 Y
-
     let mut content = Content::new();
     content
-        .transform([s as f32, 0.0, 0.0, s as f32, tx as f32, ty as f32])
+        .transform(transform)
         .x_object(svg_name);
 
     pdf.stream(content_id, &content.finish());
@@ -234,6 +389,153 @@ fn svg_to_page_pdf_bytes(svg_path: &Path, w_pt: f64, h_pt: f64) -> Result<Vec<u8
     Ok(pdf.finish())
 }
 
+/// SVG 한 장의 페이지 내 배치 (기본: contain + 중앙, 블리드 없음)
+struct SvgPlacement {
+    fit: FitMode,
+    anchor: Anchor,
+    /// 재단선 밖으로 확장할 폭(pt). 0이면 페이지 박스 그대로
+    bleed_pt: f64,
+    /// 캔버스 대신 실제 그려진 영역(잉크 AABB) 기준으로 배치
+    fit_to_ink: bool,
+}
+
+impl SvgPlacement {
+    fn from_page_config(cfg: &PageConfig, unit: &str) -> SvgPlacement {
+        SvgPlacement {
+            fit: cfg.fit.unwrap_or(FitMode::Contain),
+            anchor: cfg.anchor.unwrap_or(Anchor { x: AxisAnchor::Center, y: AxisAnchor::Center }),
+            bleed_pt: cfg.bleed.map(|b| to_points(b, unit)).unwrap_or(0.0),
+            fit_to_ink: cfg.fit_to_ink.unwrap_or(false),
+        }
+    }
+}
+
+/// usvg 트리의 실제 잉크 AABB (stroke 포함), XObject 좌표계(원점 좌하단, y 위쪽)로 반환
+///  - 캔버스 밖으로 나간 부분은 XObject BBox에서 잘리므로 캔버스와 교차
+///  - 그려진 것이 없으면 None
+fn svg_ink_bbox(tree: &svg2pdf::usvg::Tree) -> Option<(f64, f64, f64, f64)> {
+    let size = tree.size();
+    let (svg_w, svg_h) = (size.width() as f64, size.height() as f64);
+    let bb = tree.root().abs_stroke_bounding_box();
+
+    let x0 = (bb.left() as f64).max(0.0);
+    let x1 = (bb.right() as f64).min(svg_w);
+    let y0 = (bb.top() as f64).max(0.0);
+    let y1 = (bb.bottom() as f64).min(svg_h);
+    if x1 - x0 <= 0.0 || y1 - y0 <= 0.0 {
+        return None;
+    }
+    // SVG는 y가 아래로 증가 → svg2pdf XObject 안에서는 뒤집혀 있음
+    Some((x0, svg_h - y1, x1, svg_h - y0))
+}
+
+/// SVG → (벡터) **한 장짜리 페이지 PDF** 바이트 생성 (메모리)
+///  - 페이지 크기: w_pt x h_pt
+///  - 배치: **비율 유지** + `placement`의 fit/anchor (기본 contain + 중앙정렬)
+fn svg_to_page_pdf_bytes(
+    svg_path: &Path,
+    w_pt: f64,
+    h_pt: f64,
+    placement: &SvgPlacement,
+    opts: &SvgOptions,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let svg_str = std::fs::read_to_string(svg_path)?;
+    let tree = parse_svg_tree(&svg_str)?;
+    let size = tree.size();
+    let (svg_w, svg_h) = (size.width() as f64, size.height() as f64);
+
+    // ===== 변환 행렬 계산 =====
+    // XObject는 1 × 1 단위이므로 SVG 원래 크기(svg_w × svg_h) 좌표계의 U(캔버스 또는 잉크 AABB)로
+    // 균등 스케일 s 및 이동(tx, ty)을 구한 뒤, 그려질 폭/높이(svg_w·s, svg_h·s)를 행렬에 넣습니다.
+    // (이 방식은 SVG의 종횡비를 유지하고, contain이면 페이지 내에 letterbox가 생길 수 있음)
+    let canvas = (0.0, 0.0, svg_w, svg_h);
+    let u = if placement.fit_to_ink {
+        svg_ink_bbox(&tree).filter(|u| !process_pages::is_degenerate(*u)).unwrap_or(canvas)
+    } else {
+        canvas
+    };
+    let b = placement.bleed_pt;
+    let (s, tx, ty) = fit_with_anchor(
+        u,
+        (-b, -b, w_pt + b, h_pt + b),
+        placement.anchor.x, placement.anchor.y,
+        placement.fit, f64::INFINITY,
+    );
+
+    svg_tree_to_page_pdf_bytes(
+        &tree, w_pt, h_pt,
+        [(svg_w * s) as f32, 0.0, 0.0, (svg_h * s) as f32, tx as f32, ty as f32],
+        opts,
+    )
+}
+
+/// 책갈피 제목: 루트 `<svg>`의 `<title>` 텍스트 (CDATA/엔티티 해석, 공백 정리), 없으면 파일명(확장자 제외)
+fn svg_title(svg_path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let svg_str = std::fs::read_to_string(svg_path)?;
+    let xml = roxmltree::Document::parse(&svg_str)
+        .map_err(|e| format!("{}: {e}", svg_path.display()))?;
+    let from_tag = xml.root_element().children()
+        .find(|n| n.has_tag_name("title"))
+        .map(|n| {
+            let text: String = n.descendants().filter(|d| d.is_text()).filter_map(|d| d.text()).collect();
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        })
+        .filter(|t| !t.is_empty());
+    Ok(from_tag.unwrap_or_else(|| {
+        let stem = svg_path.file_stem().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        stem.trim_end_matches(".spread").to_string()
+    }))
+}
+
+/// 파일명이 `*.spread.svg`이면 양면 펼침(spread) 일러스트로 취급
+fn is_spread_svg(svg_path: &Path) -> bool {
+    svg_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .is_some_and(|s| s.to_ascii_lowercase().ends_with(".spread"))
+}
+
+/// 넓은 SVG 한 장 → 왼쪽(verso) / 오른쪽(recto) **두 장의 페이지 PDF** 바이트 생성
+///  - 펼침 전체(2w x h)에 비율 유지(contain) + 중앙정렬로 배치 후 접지선(gutter)에서 분할
+///  - `gutter_comp_pt` > 0 이면 제본으로 먹히는 폭만큼 접지선 부근 그림을 양쪽 페이지에 중복 배치
+///    (각 페이지가 접지선 너머 gutter_comp_pt 만큼을 더 보여줌 → 펼쳤을 때 그림이 이어짐)
+fn svg_spread_to_page_pdf_bytes(
+    svg_path: &Path,
+    w_pt: f64,
+    h_pt: f64,
+    gutter_comp_pt: f64,
+    opts: &SvgOptions,
+) -> Result<(Vec<u8>, Vec<u8>), Box<dyn std::error::Error>> {
+    let svg_str = std::fs::read_to_string(svg_path)?;
+    let tree = parse_svg_tree(&svg_str)?;
+    let size = tree.size();
+    let (svg_w, svg_h) = (size.width() as f64, size.height() as f64);
+
+    // 펼침 가용 영역: 보정폭만큼 좁아진 (2w - 2c) x h
+    let avail_w = 2.0 * w_pt - 2.0 * gutter_comp_pt;
+    if avail_w <= w_pt {
+        return Err(format!("gutter compensation too large for spread {}", svg_path.display()).into());
+    }
+    let scale = (avail_w / svg_w).min(h_pt / svg_h);
+    let (dw, dh) = (svg_w * scale, svg_h * scale);
+    let x0 = (avail_w - dw) / 2.0;
+    let y0 = (h_pt - dh) / 2.0;
+
+    // 왼쪽 페이지: 가용 영역 [0, w], 오른쪽 페이지: [w - 2c, 2w - 2c]
+    let shift = w_pt - 2.0 * gutter_comp_pt;
+    let left = svg_tree_to_page_pdf_bytes(
+        &tree, w_pt, h_pt,
+        [dw as f32, 0.0, 0.0, dh as f32, x0 as f32, y0 as f32],
+        opts,
+    )?;
+    let right = svg_tree_to_page_pdf_bytes(
+        &tree, w_pt, h_pt,
+        [dw as f32, 0.0, 0.0, dh as f32, (x0 - shift) as f32, y0 as f32],
+        opts,
+    )?;
+    Ok((left, right))
+}
+
 fn make_pdf(args: Args, output: String) -> Result<(), Box<dyn std::error::Error>> {
     let unit = args.unit_system.as_str();
     let w_pt = to_points(args.width, unit);
@@ -245,68 +547,237 @@ fn make_pdf(args: Args, output: String) -> Result<(), Box<dyn std::error::Error>
     let svgs_glob = "./materials/svg/*.svg";
     let out   = PathBuf::from(output);
 
-    // temp (front/back 안정화용)
-    let temp_dir = PathBuf::from("./temp");
-    fs::create_dir_all(&temp_dir)?;
-    let temp_front = temp_dir.join("front_matter.parsed.pdf");
-    let temp_back  = temp_dir.join("back_matter.parsed.pdf");
-    roundtrip_save(&front, &temp_front)?;
-    roundtrip_save(&back,  &temp_back )?;
+    let project = ProjectConfig::load(&args.config)?;
+    let direction = args.direction.or(project.direction).unwrap_or_default();
+
+    let blanks = BackingPages::new(&project.blanks, w_pt, h_pt, &project.svg)?;
 
     // front 로드 + 페이지 크기 통일
-    let mut merged = Document::load(&temp_front)?;
-    enforce_page_size(&mut merged, w_pt, h_pt)?;
+    let mut front_doc = normalize::load_normalized(&front)?;
+    let mismatched = process_pages::fit_pages_to_size(&mut front_doc, w_pt, h_pt, project.matter.resize)?;
+    warn_mismatched("front_matter.pdf", &mismatched, w_pt, h_pt, project.matter.resize);
+    enforce_page_size(&mut front_doc, w_pt, h_pt)?;
+    page_meta::tag_all_from(&mut front_doc, &PageMeta::new(PageKind::Front), "front_matter.pdf")?;
+    // 페이지 출처는 모아 두었다가 마지막에 한 번에 병합
+    let mut asm = Assembly::new(front_doc, blanks.template);
 
     // make-even: front가 홀수면 1장 추가
-    if args.make_even {
-        let front_pages = merged.get_pages().len();
-        if front_pages % 2 == 1 {
-            let blank = blank_page_doc(w_pt, h_pt);
-            merged = append_doc(merged, blank)?;
-        }
+    if args.make_even && asm.page_count() % 2 == 1 {
+        asm.push_blank("front");
     }
 
-    // SVG들: 메모리에서 **페이지 단위 PDF** 생성(변환 포함) → 병합
+    // SVG들: 메모리에서 **페이지 단위 PDF** 생성(변환 포함) → 조립
     let mut svg_paths: Vec<PathBuf> = glob(svgs_glob)?.filter_map(|e| e.ok()).collect();
     svg_paths.sort();
 
-    // isARC=true → 사이 빈페이지 X, isARC=false → 사이 빈페이지 O
-    let insert_between = !args.arc;
+    // 빈 페이지 정책: CLI ← config ← 기본값(ARC면 none, 아니면 single-sided)
+    let blank_policy = args.blank_policy
+        .or(project.blanks.policy)
+        .unwrap_or(if args.arc { BlankPolicy::None } else { BlankPolicy::SingleSided });
+    let trailing_blank = project.blanks.trailing.unwrap_or(true);
+    let even_page_count = project.blanks.even_page_count.unwrap_or(!args.arc);
+
+    let spread_comp_pt = to_points(args.spread_gutter_comp, unit);
+
+    // 섹션이 바뀔 때마다 새 섹션의 시작 면 규칙 적용
+    let mut current_section = String::from("front");
+    // 풀블리드 페이지의 블리드(pt). 책 전체 MediaBox를 이 값으로 통일하므로 하나만 허용
+    let mut interior_bleed_pt: Option<f64> = None;
 
     for (i, svg) in svg_paths.iter().enumerate() {
-        let svg_page_bytes = svg_to_page_pdf_bytes(svg, w_pt, h_pt)?;
-        let svg_page_doc = Document::load_mem(&svg_page_bytes)?;
-        merged = append_doc(merged, svg_page_doc)?;
+        let is_last = i + 1 == svg_paths.len();
+        let page_cfg = project.page_config(svg)?;
+        let svg_opts = project.svg_options(&page_cfg);
+        let source = svg.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        // 제목은 일러스트별 책갈피에만 쓰임
+        let title = if project.outline.enabled && project.outline.pages { svg_title(svg)? } else { String::new() };
+
+        let section = page_cfg.section.clone().unwrap_or_else(|| String::from("body"));
+        if section != current_section {
+            if blank_policy == BlankPolicy::BetweenSections {
+                asm.push_blank(&current_section);
+            }
+            pad_section_start(&mut asm, project.section_start(&section), &current_section);
+            current_section = section;
+        }
+
+        // spread: 첫 반쪽이 짝수 페이지(verso)에 오도록, before-recto 정책의 낱장은 홀수 페이지(recto)에 오도록 앞에 빈 페이지
+        let spread = is_spread_svg(svg);
+        if spread {
+            pad_section_start(&mut asm, SectionStart::Verso, &current_section);
+        } else if blank_policy == BlankPolicy::BeforeRecto {
+            pad_section_start(&mut asm, SectionStart::Recto, &current_section);
+        }
+
+        let mut meta = PageMeta::new(PageKind::Art);
+        meta.section = current_section.clone();
+        meta.source = source;
+        meta.title = title;
+        meta.folio = page_cfg.folio.unwrap_or(true);
+        if spread {
+            let (left_bytes, right_bytes) = svg_spread_to_page_pdf_bytes(
+                svg, w_pt, h_pt, spread_comp_pt, &svg_opts,
+            )?;
+            // 접지선을 가로질러 그림이 이어져야 하므로 여백 스케일링 제외
+            meta.inner_margin = false;
+            // RTL: 짝수 페이지가 오른쪽 → 오른쪽 반쪽이 먼저
+            let halves = match direction {
+                BindingDirection::Ltr => [left_bytes, right_bytes],
+                BindingDirection::Rtl => [right_bytes, left_bytes],
+            };
+            for bytes in halves {
+                let mut half = Document::load_mem(&bytes)?;
+                page_meta::tag_all(&mut half, &meta)?;
+                asm.push(half);
+            }
+        } else {
+            let placement = SvgPlacement::from_page_config(&page_cfg, unit);
+            let svg_page_bytes = svg_to_page_pdf_bytes(svg, w_pt, h_pt, &placement, &svg_opts)?;
+            let mut svg_page_doc = Document::load_mem(&svg_page_bytes)?;
+            meta.full_bleed = page_cfg.bleed.is_some();
+            if meta.full_bleed {
+                match interior_bleed_pt {
+                    Some(b) if (b - placement.bleed_pt).abs() > 1e-6 => {
+                        return Err(format!(
+                            "{}: bleed differs from other full-bleed pages ({:.3} vs {:.3} pt); all pages share one bleed",
+                            svg.display(), placement.bleed_pt, b,
+                        ).into());
+                    }
+                    _ => interior_bleed_pt = Some(placement.bleed_pt),
+                }
+            }
+            meta.inner_margin = page_cfg.inner_margin.unwrap_or(true);
+            meta.fit = page_cfg.fit;
+            meta.anchor = page_cfg.anchor;
+            page_meta::tag_all(&mut svg_page_doc, &meta)?;
+            asm.push(svg_page_doc);
+        }
 
-        if insert_between && i + 1 <= svg_paths.len() {
-            let blank = blank_page_doc(w_pt, h_pt);
-            merged = append_doc(merged, blank)?;
+        // 펼침도 낱장과 같은 규칙으로 뒤에 빈 페이지 (single-sided, trailing, blank_after)
+        let blank_after = match blank_policy {
+            BlankPolicy::SingleSided => !is_last || trailing_blank,
+            _ => false,
+        };
+        if page_cfg.blank_after.unwrap_or(blank_after) {
+            asm.push_blank(&current_section);
         }
     }
 
     // back 로드 + 크기 통일 후 병합
-    let mut back_doc = Document::load(&temp_back)?;
+    let mut back_doc = normalize::load_normalized(&back)?;
+    let mismatched = process_pages::fit_pages_to_size(&mut back_doc, w_pt, h_pt, project.matter.resize)?;
+    warn_mismatched("back_matter.pdf", &mismatched, w_pt, h_pt, project.matter.resize);
     enforce_page_size(&mut back_doc, w_pt, h_pt)?;
-    merged = append_doc(merged, back_doc)?;
+    page_meta::tag_all_from(&mut back_doc, &PageMeta::new(PageKind::Back), "back_matter.pdf")?;
+    if blank_policy == BlankPolicy::BetweenSections && current_section != "back" {
+        asm.push_blank(&current_section);
+    }
+    pad_section_start(&mut asm, project.section_start("back"), &current_section);
+    asm.push(back_doc);
+
+    // KDP: 전체 페이지 수 짝수 유지 (맨 끝에 1장)
+    if even_page_count && asm.page_count() % 2 == 1 {
+        asm.push_blank("back");
+    }
+    let mut merged = asm.finish()?;
 
     // 최종 크기 통일(안전)
     enforce_page_size(&mut merged, w_pt, h_pt)?;
 
     if args.arc {
-        process_pages::post_process_arc(&mut merged)?;
+        let removed = process_pages::post_process_arc(&mut merged, &project, args.blank_dry_run)?;
+        print_blank_report(&removed, args.blank_dry_run);
     } else {
-        let book_params = BookParams::new(args.width, args.height, args.unit_system, args.num_pages);
-        let book = Book::new(book_params, BINDING_PARAMS_KDP_WHITE);
-        process_pages::post_process_book(&mut merged, book)?;
+        // Book은 인치 기준: 판형은 단위계와 무관하게 인치로, 블리드는 그림 배치에 쓴 값 그대로
+        let book_params = BookParams::new(w_pt / 72.0, h_pt / 72.0, args.num_pages, direction);
+        let binding = BookBindingConstant {
+            bleed_interior: interior_bleed_pt.unwrap_or(0.0) / 72.0,
+            ..args.paper.binding()
+        };
+        let book = Book::new(book_params, binding);
+
+        // 표지 작업용 안내 템플릿 (판형·쪽수·제본 방향 기준, 우철이면 앞표지가 왼쪽)
+        let (cover_w, cover_h) = cover::template_size(&book);
+        single_page_doc(cover_w, cover_h, cover::template_content(&book), cover::template_resources())
+            .save("./book_cover_template.pdf")?;
+        println!(
+            "Cover template: {:.3} x {:.3} in (spine {:.3} in)",
+            cover_w / 72.0, cover_h / 72.0, book.get_spine_width(),
+        );
+        let cli_fit = FitPolicyConfig {
+            sparse_threshold: args.sparse_threshold,
+            mode: args.fit_mode,
+            ..Default::default()
+        };
+        process_pages::post_process_book(
+            &mut merged,
+            book,
+            &project.fit_policies(&cli_fit),
+            &project,
+        )?;
+    }
+
+    if direction == BindingDirection::Rtl {
+        set_viewer_direction_r2l(&mut merged)?;
+    }
+
+    if project.outline.enabled {
+        outline::build_outline(&mut merged, &project)?;
     }
 
+    page_meta::strip(&mut merged);
     merged.save(out)?;
     println!("Done.");
     Ok(())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    make_pdf(Args::book(), String::from("./book.pdf"))?;
-    make_pdf(Args::arc(), String::from("./book_ARC.pdf"))?;
+    let args = Args::parse();
+    if args.only != Some(Output::Arc) {
+        make_pdf(Args { arc: false, ..args.clone() }, String::from("./book.pdf"))?;
+    }
+    if args.only != Some(Output::Book) {
+        make_pdf(Args { arc: true, ..args }, String::from("./book_ARC.pdf"))?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 임시 디렉터리에 SVG 파일을 쓰고 경로를 돌려줌 (테스트마다 다른 이름)
+    fn write_svg(name: &str, body: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bb_lopdf_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">{body}</svg>"#)).unwrap();
+        path
+    }
+
+    #[test]
+    fn svg_title_reads_root_title() {
+        let path = write_svg("titled.svg", "<title>\n  Fish &amp; <![CDATA[Chips <3]]>\n</title><rect width=\"1\" height=\"1\"/>");
+        assert_eq!(svg_title(&path).unwrap(), "Fish & Chips <3");
+    }
+
+    #[test]
+    fn svg_title_ignores_nested_titles() {
+        let path = write_svg("nested.svg", "<g><title>Group</title></g>");
+        assert_eq!(svg_title(&path).unwrap(), "nested");
+    }
+
+    #[test]
+    fn svg_title_falls_back_to_file_name() {
+        let path = write_svg("04-harbour.spread.svg", "<title>   </title>");
+        assert_eq!(svg_title(&path).unwrap(), "04-harbour");
+    }
+
+    #[test]
+    fn spread_suffix() {
+        assert!(is_spread_svg(Path::new("a/03.spread.svg")));
+        assert!(is_spread_svg(Path::new("03.SPREAD.svg")));
+        assert!(!is_spread_svg(Path::new("spread.svg")));
+        assert!(!is_spread_svg(Path::new("03.svg")));
+    }
+}
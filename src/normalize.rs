@@ -0,0 +1,333 @@
+use lopdf::xref::XrefEntry;
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::Path;
+use crate::links;
+use crate::page_tree;
+use crate::process_pages::as_f64;
+
+/// 외부 PDF(front/back matter)를 읽어 메모리에서 정규화.
+///  - xref가 깨져 있으면(로드 실패, 읽히지 않는 항목) 본문을 훑어 `N G obj` 위치로 xref 재구성
+///  - 객체 스트림(/ObjStm)은 펼쳐진 개별 객체만 남기고 컨테이너와 xref 스트림은 제거
+///  - 없는 객체를 가리키는 참조는 세대 번호를 고치거나 제거
+///  - 상속 속성(Resources, MediaBox, CropBox, Rotate)을 각 페이지로 내리고 페이지 트리를 평평하게
+///  - /Rotate와 원점이 (0, 0)이 아닌 페이지 박스를 콘텐츠 변환으로 옮겨 모든 페이지를 `[0 0 w h]`로
+pub fn load_normalized(path: &Path) -> Result<Document, Box<dyn Error>> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut doc = match Document::load_mem(&bytes) {
+        Ok(doc) if !has_unread_entries(&doc) => doc,
+        _ => Document::load_mem(&with_rebuilt_xref(&bytes)?)
+            .map_err(|e| format!("{}: cannot repair xref: {e}", path.display()))?,
+    };
+    if doc.is_encrypted() {
+        return Ok(doc);
+    }
+
+    drop_container_streams(&mut doc);
+    fix_references(&mut doc);
+    page_tree::flatten(&mut doc)?;
+    upright_pages(&mut doc)?;
+    doc.max_id = doc.objects.keys().map(|(n, _)| *n).max().unwrap_or(0);
+    Ok(doc)
+}
+
+/// xref의 일반 항목 중 읽지 못한 객체가 있는지 (오프셋이 틀린 xref)
+fn has_unread_entries(doc: &Document) -> bool {
+    let loaded: HashSet<u32> = doc.objects.keys().map(|(n, _)| *n).collect();
+    doc.reference_table.entries.iter()
+        .any(|(n, e)| matches!(e, XrefEntry::Normal { .. }) && !loaded.contains(n))
+}
+
+/// `pos`가 줄/토큰 시작인지
+fn at_token_start(bytes: &[u8], pos: usize) -> bool {
+    pos == 0 || bytes[pos - 1].is_ascii_whitespace()
+}
+
+/// 본문에서 `N G obj` 헤더를 모두 찾아 (번호 → (세대, 오프셋)). 같은 번호는 뒤의 것(증분 갱신)이 이김
+fn scan_objects(bytes: &[u8]) -> HashMap<u32, (u16, usize)> {
+    let mut found = HashMap::new();
+    let mut i = 0;
+    while let Some(rel) = bytes[i..].windows(3).position(|w| w == b"obj") {
+        let at = i + rel;
+        i = at + 3;
+        // "obj" 바로 앞: 공백 + 세대 + 공백 + 번호
+        let mut p = at;
+        let skip_ws = |p: &mut usize| while *p > 0 && bytes[*p - 1].is_ascii_whitespace() { *p -= 1 };
+        let digits = |p: &mut usize| {
+            let end = *p;
+            while *p > 0 && bytes[*p - 1].is_ascii_digit() { *p -= 1 }
+            std::str::from_utf8(&bytes[*p..end]).ok().filter(|s| !s.is_empty()).map(str::to_string)
+        };
+        if p == 0 || !bytes[p - 1].is_ascii_whitespace() {
+            continue; // endobj 등
+        }
+        skip_ws(&mut p);
+        let Some(generation) = digits(&mut p).and_then(|g| g.parse::<u16>().ok()) else { continue };
+        if p == 0 || !bytes[p - 1].is_ascii_whitespace() {
+            continue;
+        }
+        skip_ws(&mut p);
+        let Some(number) = digits(&mut p).and_then(|n| n.parse::<u32>().ok()) else { continue };
+        if number == 0 || !at_token_start(bytes, p) {
+            continue;
+        }
+        found.insert(number, (generation, p));
+    }
+    found
+}
+
+/// 찾은 객체 중 카탈로그 (/Type /Catalog)
+fn find_catalog(bytes: &[u8], objects: &HashMap<u32, (u16, usize)>) -> Option<(u32, u16)> {
+    let mut by_offset: Vec<(&u32, &(u16, usize))> = objects.iter().collect();
+    by_offset.sort_by_key(|(_, (_, off))| *off);
+    by_offset.into_iter().rev().find_map(|(n, (g, off))| {
+        let body = &bytes[*off..];
+        let end = body.windows(6).position(|w| w == b"endobj").unwrap_or(body.len());
+        let compact: Vec<u8> = body[..end].iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+        compact.windows(13).any(|w| w == b"/Type/Catalog").then_some((*n, *g))
+    })
+}
+
+/// 원본 뒤에 새 xref 테이블 + trailer를 덧붙인 사본 (증분 갱신처럼 마지막 xref가 쓰임)
+fn with_rebuilt_xref(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let objects = scan_objects(bytes);
+    let (root_n, root_g) = find_catalog(bytes, &objects).ok_or("no catalog object found")?;
+    let size = objects.keys().max().map_or(1, |m| m + 1);
+
+    let mut out = bytes.to_vec();
+    out.push(b'\n');
+    let xref_start = out.len();
+    out.extend_from_slice(format!("xref\n0 {size}\n0000000000 65535 f \n").as_bytes());
+    for n in 1..size {
+        let line = match objects.get(&n) {
+            Some((g, off)) => format!("{off:010} {g:05} n \n"),
+            None => String::from("0000000000 65535 f \n"),
+        };
+        out.extend_from_slice(line.as_bytes());
+    }
+    out.extend_from_slice(
+        format!("trailer\n<< /Size {size} /Root {root_n} {root_g} R >>\nstartxref\n{xref_start}\n%%EOF\n").as_bytes(),
+    );
+    Ok(out)
+}
+
+/// 펼쳐 놓은 /ObjStm 컨테이너와 /XRef 스트림 제거 (저장할 때 다시 만들어짐)
+fn drop_container_streams(doc: &mut Document) {
+    doc.objects.retain(|_, obj| match obj.as_stream() {
+        Ok(s) => !(s.dict.has_type(b"ObjStm") || s.dict.has_type(b"XRef")),
+        Err(_) => true,
+    });
+}
+
+/// 없는 객체를 가리키는 참조 정리: 번호만 맞으면 세대를 고치고, 아니면 제거 (배열 원소 / 사전 항목)
+fn fix_references(doc: &mut Document) {
+    let by_number: HashMap<u32, ObjectId> = doc.objects.keys().map(|id| (id.0, *id)).collect();
+    for obj in doc.objects.values_mut() {
+        fix_object(obj, &by_number);
+    }
+    fix_dict_refs(&mut doc.trailer, &by_number);
+}
+
+/// 참조를 고치고, `obj` 자체가 없는 객체를 가리키는 참조면 false
+fn fix_object(obj: &mut Object, by_number: &HashMap<u32, ObjectId>) -> bool {
+    match obj {
+        Object::Reference(id) => match by_number.get(&id.0) {
+            Some(found) => {
+                *id = *found;
+                true
+            }
+            None => false,
+        },
+        Object::Array(items) => {
+            items.retain_mut(|item| fix_object(item, by_number));
+            true
+        }
+        Object::Dictionary(dict) => {
+            fix_dict_refs(dict, by_number);
+            true
+        }
+        Object::Stream(stream) => {
+            fix_dict_refs(&mut stream.dict, by_number);
+            true
+        }
+        _ => true,
+    }
+}
+
+fn fix_dict_refs(dict: &mut lopdf::Dictionary, by_number: &HashMap<u32, ObjectId>) {
+    let dangling: Vec<Vec<u8>> = dict.iter_mut()
+        .filter_map(|(key, value)| (!fix_object(value, by_number)).then(|| key.clone()))
+        .collect();
+    for key in dangling {
+        dict.remove(&key);
+    }
+}
+
+fn page_rect(page: &Dictionary, key: &[u8]) -> Option<(f64, f64, f64, f64)> {
+    let a = page.get(key).ok()?.as_array().ok()?;
+    if a.len() != 4 {
+        return None;
+    }
+    let (x0, y0, x1, y1) = (as_f64(&a[0])?, as_f64(&a[1])?, as_f64(&a[2])?, as_f64(&a[3])?);
+    Some((x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)))
+}
+
+/// 보이는 영역(CropBox ∩ MediaBox)을 똑바로 세워 원점으로 옮기는 행렬과 새 크기.
+/// /Rotate는 시계 방향 90도 단위 (그 밖의 값은 0으로 취급)
+fn upright_matrix((llx, lly, urx, ury): (f64, f64, f64, f64), rotate: i64) -> ([f64; 6], f64, f64) {
+    let (w, h) = (urx - llx, ury - lly);
+    match rotate.rem_euclid(360) {
+        90 => ([0.0, -1.0, 1.0, 0.0, -lly, urx], h, w),
+        180 => ([-1.0, 0.0, 0.0, -1.0, urx, ury], w, h),
+        270 => ([0.0, 1.0, -1.0, 0.0, ury, -llx], h, w),
+        _ => ([1.0, 0.0, 0.0, 1.0, -llx, -lly], w, h),
+    }
+}
+
+/// 회전됐거나 박스 원점이 (0, 0)이 아닌 페이지의 콘텐츠를 Form XObject로 감싸
+/// 회전·이동을 콘텐츠에 굽고, 박스는 `[0 0 w h]`, /Rotate는 제거.
+/// 이후 크기 맞춤·여백·워터마크는 회전과 원점을 신경 쓰지 않아도 됨
+pub fn upright_pages(doc: &mut Document) -> Result<(), Box<dyn Error>> {
+    let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    for pid in page_ids {
+        let page = doc.get_object(pid)?.as_dict()?;
+        let Some(media) = page_rect(page, b"MediaBox") else { continue };
+        let visible = match page_rect(page, b"CropBox") {
+            // CropBox는 MediaBox 안쪽만 유효
+            Some(c) => (c.0.max(media.0), c.1.max(media.1), c.2.min(media.2), c.3.min(media.3)),
+            None => media,
+        };
+        if visible.2 <= visible.0 || visible.3 <= visible.1 {
+            continue;
+        }
+        let rotate = page.get(b"Rotate").ok().and_then(as_f64).unwrap_or(0.0) as i64;
+        let (m, w, h) = upright_matrix(visible, rotate);
+        if m == [1.0, 0.0, 0.0, 1.0, 0.0, 0.0] && media == visible {
+            continue;
+        }
+
+        // Trim/Bleed/ArtBox도 같은 변환으로
+        let mapped_boxes: Vec<(&[u8], Object)> = [b"TrimBox".as_slice(), b"BleedBox", b"ArtBox"].into_iter()
+            .filter_map(|key| {
+                let (x0, y0, x1, y1) = page_rect(page, key)?;
+                let p = |x: f64, y: f64| (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5]);
+                let (a, b) = (p(x0, y0), p(x1, y1));
+                let rect = [a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1)];
+                Some((key, Object::Array(rect.iter().map(|v| Object::Real(*v as f32)).collect())))
+            })
+            .collect();
+
+        let content = doc.get_page_content(pid)?;
+        let mut form_dict = Dictionary::new();
+        form_dict.set("Type", "XObject");
+        form_dict.set("Subtype", "Form");
+        form_dict.set("FormType", 1);
+        form_dict.set("BBox", Object::Array(vec![visible.0.into(), visible.1.into(), visible.2.into(), visible.3.into()]));
+        if let Ok(res) = doc.get_object(pid)?.as_dict()?.get(b"Resources") {
+            form_dict.set("Resources", res.clone());
+        }
+        let form_id = doc.add_object(Stream::new(form_dict, content));
+
+        let mut xobjs = Dictionary::new();
+        xobjs.set("UPRIGHT", Object::Reference(form_id));
+        let mut res = Dictionary::new();
+        res.set("XObject", Object::Dictionary(xobjs));
+        let m = m.map(|v| v + 0.0); // -0 → 0
+        let draw = format!(
+            "q\n{} {} {} {} {} {} cm\n/UPRIGHT Do\nQ\n",
+            m[0], m[1], m[2], m[3], m[4], m[5]
+        );
+        let draw_id = doc.add_object(Stream::new(Dictionary::new(), draw.into_bytes()));
+
+        let page_box = Object::Array(vec![0.into(), 0.into(), w.into(), h.into()]);
+        {
+            let pd = doc.get_object_mut(pid)?.as_dict_mut()?;
+            pd.set("Resources", Object::Dictionary(res));
+            pd.set("Contents", Object::Reference(draw_id));
+            pd.set("MediaBox", page_box.clone());
+            pd.set("CropBox", page_box);
+            pd.remove(b"Rotate");
+            for (key, rect) in mapped_boxes {
+                pd.set(key, rect);
+            }
+        }
+        links::transform_annotations_by(doc, pid, m)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    const BOX: (f64, f64, f64, f64) = (10.0, 20.0, 110.0, 220.0);
+
+    fn apply(m: &[f64; 6], (x, y): (f64, f64)) -> (f64, f64) {
+        (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5])
+    }
+
+    #[test]
+    fn upright_matrix_moves_box_to_origin() {
+        let (llx, lly, urx, ury) = BOX;
+        // 회전마다 화면에서 왼쪽 위에 보이는 원래 모서리
+        let top_left = [(0, (llx, ury)), (90, (llx, lly)), (180, (urx, lly)), (270, (urx, ury))];
+        for (rotate, corner) in top_left {
+            let (m, w, h) = upright_matrix(BOX, rotate);
+            let expected = if rotate % 180 == 0 { (100.0, 200.0) } else { (200.0, 100.0) };
+            assert_eq!((w, h), expected, "size for /Rotate {rotate}");
+            assert_eq!(apply(&m, corner), (0.0, h), "top-left for /Rotate {rotate}");
+
+            // 네 모서리가 [0 0 w h]의 네 모서리로
+            let mut mapped: Vec<(f64, f64)> = [(llx, lly), (urx, lly), (llx, ury), (urx, ury)]
+                .into_iter().map(|p| apply(&m, p)).collect();
+            mapped.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(mapped, [(0.0, 0.0), (0.0, h), (w, 0.0), (w, h)], "corners for /Rotate {rotate}");
+        }
+    }
+
+    #[test]
+    fn upright_matrix_normalizes_rotation() {
+        assert_eq!(upright_matrix(BOX, -90), upright_matrix(BOX, 270));
+        assert_eq!(upright_matrix(BOX, 450), upright_matrix(BOX, 90));
+        // 90도 단위가 아니면 회전 없음
+        assert_eq!(upright_matrix(BOX, 45), upright_matrix(BOX, 0));
+    }
+
+    #[test]
+    fn upright_pages_bakes_rotation_into_content() {
+        let mut doc = Document::with_version("1.7");
+        let root = doc.new_object_id();
+        let pid = doc.new_object_id();
+        let link = doc.add_object(dictionary! {
+            "Type" => "Annot", "Subtype" => "Link",
+            "Rect" => vec![10.into(), 20.into(), 20.into(), 30.into()],
+            "Dest" => vec![pid.into(), "XYZ".into(), 10.into(), 20.into(), Object::Null],
+        });
+        let contents = doc.add_object(Stream::new(Dictionary::new(), b"0 0 1 1 re f".to_vec()));
+        doc.objects.insert(pid, Object::Dictionary(dictionary! {
+            "Type" => "Page",
+            "MediaBox" => vec![10.into(), 20.into(), 110.into(), 220.into()],
+            "Rotate" => 90,
+            "Contents" => contents,
+            "Annots" => vec![link.into()],
+        }));
+        page_tree::build_balanced(&mut doc, root, &[pid]).unwrap();
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => root });
+        doc.trailer.set("Root", catalog);
+
+        upright_pages(&mut doc).unwrap();
+
+        let page = doc.get_object(pid).unwrap().as_dict().unwrap();
+        assert!(!page.has(b"Rotate"));
+        assert_eq!(page_rect(page, b"MediaBox"), Some((0.0, 0.0, 200.0, 100.0)));
+        let annot = doc.get_object(link).unwrap().as_dict().unwrap();
+        let rect: Vec<f64> = annot.get(b"Rect").unwrap().as_array().unwrap().iter().filter_map(as_f64).collect();
+        assert_eq!(rect, [0.0, 90.0, 10.0, 100.0]);
+        // 원래 왼쪽 아래 = 화면 왼쪽 위
+        let dest = annot.get(b"Dest").unwrap().as_array().unwrap();
+        assert_eq!((as_f64(&dest[2]), as_f64(&dest[3])), (Some(0.0), Some(100.0)));
+        assert_eq!(dest[4], Object::Null);
+    }
+}
@@ -0,0 +1,305 @@
+use lopdf::{dictionary, Document, Object, ObjectId};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use crate::config::ProjectConfig;
+use crate::links;
+use crate::page_meta::{self, PageKind};
+
+/// 카탈로그의 /Outlines 루트
+fn outline_root(doc: &Document) -> Option<ObjectId> {
+    doc.catalog().ok()?.get(b"Outlines").ok()?.as_reference().ok()
+}
+
+fn item_ref(doc: &Document, id: ObjectId, key: &[u8]) -> Option<ObjectId> {
+    doc.get_object(id).ok()?.as_dict().ok()?.get(key).ok()?.as_reference().ok()
+}
+
+/// `parent`의 직계 자식 항목 (First → Next 순, 순환 방지)
+fn children(doc: &Document, parent: ObjectId) -> Vec<ObjectId> {
+    let mut out = Vec::new();
+    let mut seen = HashSet::new();
+    let mut cur = item_ref(doc, parent, b"First");
+    while let Some(id) = cur {
+        if !seen.insert(id) {
+            break;
+        }
+        out.push(id);
+        cur = item_ref(doc, id, b"Next");
+    }
+    out
+}
+
+/// 모든 책갈피 항목 (하위 포함)
+pub fn all_items(doc: &Document) -> Vec<ObjectId> {
+    let mut out = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack = top_level_items(doc);
+    while let Some(id) = stack.pop() {
+        if seen.insert(id) {
+            out.push(id);
+            stack.extend(children(doc, id));
+        }
+    }
+    out
+}
+
+/// 문서 책갈피의 최상위 항목들
+pub fn top_level_items(doc: &Document) -> Vec<ObjectId> {
+    outline_root(doc).map(|root| children(doc, root)).unwrap_or_default()
+}
+
+/// 펼쳐져 있을 때 보이는 하위 항목 수 (닫혀 있으면 0)
+fn visible_count(doc: &Document, id: ObjectId) -> i64 {
+    doc.get_object(id)
+        .and_then(|o| o.as_dict())
+        .and_then(|d| d.get(b"Count"))
+        .and_then(|c| c.as_i64())
+        .map(|c| c.max(0))
+        .unwrap_or(0)
+}
+
+/// `parent` 아래에 `items`를 순서대로 연결 (Parent/Prev/Next, First/Last/Count 갱신, 펼친 상태)
+fn link_children(doc: &mut Document, parent: ObjectId, items: &[ObjectId]) -> Result<(), Box<dyn Error>> {
+    for (i, id) in items.iter().enumerate() {
+        let item = doc.get_object_mut(*id)?.as_dict_mut()?;
+        item.set("Parent", parent);
+        match i.checked_sub(1).map(|p| items[p]) {
+            Some(prev) => item.set("Prev", prev),
+            None => { item.remove(b"Prev"); }
+        }
+        match items.get(i + 1) {
+            Some(next) => item.set("Next", *next),
+            None => { item.remove(b"Next"); }
+        }
+    }
+    let count: i64 = items.iter().map(|id| 1 + visible_count(doc, *id)).sum();
+    let pd = doc.get_object_mut(parent)?.as_dict_mut()?;
+    match (items.first(), items.last()) {
+        (Some(first), Some(last)) => {
+            pd.set("First", *first);
+            pd.set("Last", *last);
+            pd.set("Count", count);
+        }
+        _ => {
+            pd.remove(b"First");
+            pd.remove(b"Last");
+            pd.remove(b"Count");
+        }
+    }
+    Ok(())
+}
+
+/// 책갈피 루트 끝에 최상위 항목 추가 (루트가 없으면 생성)
+pub fn append_top_level(doc: &mut Document, items: &[ObjectId]) -> Result<(), Box<dyn Error>> {
+    let root = match outline_root(doc) {
+        Some(root) => root,
+        None => {
+            let root = doc.add_object(dictionary! { "Type" => "Outlines" });
+            doc.catalog_mut()?.set("Outlines", root);
+            root
+        }
+    };
+    let mut all = children(doc, root);
+    all.extend_from_slice(items);
+    link_children(doc, root, &all)
+}
+
+/// PDF 텍스트 문자열 (ASCII 밖 문자가 있으면 UTF-16BE + BOM)
+pub fn text_string(s: &str) -> Object {
+    if s.is_ascii() {
+        return Object::string_literal(s);
+    }
+    let mut bytes = vec![0xFE, 0xFF];
+    for u in s.encode_utf16() {
+        bytes.extend_from_slice(&u.to_be_bytes());
+    }
+    Object::String(bytes, lopdf::StringFormat::Hexadecimal)
+}
+
+fn new_item(doc: &mut Document, title: &str, page_id: ObjectId) -> ObjectId {
+    doc.add_object(dictionary! {
+        "Title" => text_string(title),
+        "Dest" => Object::Array(vec![Object::Reference(page_id), "Fit".into()]),
+    })
+}
+
+/// 섹션 구조로 책갈피 재구성.
+///  - 같은 섹션이 연속된 구간마다 최상위 항목 1개 (`[sections.<name>] title`, 없으면 섹션 이름)
+///  - front/back matter에서 가져온 기존 책갈피는 대상 페이지가 속한 구간 아래로 옮김
+///    (대상 페이지가 사라졌고 하위 항목도 없으면 버림, 대상을 못 찾았지만 하위 항목이 있으면 맨 끝에 유지)
+///  - `[outline] pages = true`면 일러스트마다 항목 추가 (spread는 한 번)
+///
+/// 페이지 삭제 이후(ARC) 호출해야 한다
+pub fn build_outline(doc: &mut Document, project: &ProjectConfig) -> Result<(), Box<dyn Error>> {
+    let pages: Vec<(ObjectId, page_meta::PageMeta)> = doc.get_pages().values()
+        .map(|pid| (*pid, page_meta::get(doc, *pid)))
+        .collect();
+    if pages.is_empty() {
+        return Ok(());
+    }
+    let page_index: HashMap<ObjectId, usize> = pages.iter().enumerate().map(|(i, (pid, _))| (*pid, i)).collect();
+
+    // 1) 섹션 구간 [start, end)
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (i, (_, meta)) in pages.iter().enumerate() {
+        match runs.last_mut() {
+            Some((start, end)) if pages[*start].1.section == meta.section => *end = i + 1,
+            _ => runs.push((i, i + 1)),
+        }
+    }
+
+    // 2) 기존 최상위 책갈피를 구간별로 분배
+    let mut run_items: Vec<Vec<ObjectId>> = vec![Vec::new(); runs.len()];
+    let mut leftovers = Vec::new();
+    for item in top_level_items(doc) {
+        let target = doc.get_object(item).and_then(|o| o.as_dict()).ok()
+            .and_then(|d| links::target_page(doc, d));
+        match target.and_then(|pid| page_index.get(&pid)) {
+            Some(&idx) => {
+                let run = runs.iter().position(|(s, e)| (*s..*e).contains(&idx)).unwrap_or(0);
+                run_items[run].push(item);
+            }
+            None if item_ref(doc, item, b"First").is_some() => leftovers.push(item),
+            None => {}
+        }
+    }
+
+    // 3) 섹션 항목 + 일러스트 항목
+    let mut top = Vec::new();
+    for ((start, end), mut items) in runs.iter().copied().zip(run_items) {
+        let (first_pid, first_meta) = &pages[start];
+        let entry = new_item(doc, project.section_title(&first_meta.section), *first_pid);
+
+        if project.outline.pages {
+            let mut last_source: Option<&str> = None;
+            for (pid, meta) in &pages[start..end] {
+                if meta.kind != PageKind::Art || meta.title.is_empty() {
+                    continue;
+                }
+                if last_source == Some(meta.source.as_str()) {
+                    continue; // spread 두 번째 반쪽
+                }
+                last_source = Some(meta.source.as_str());
+                items.push(new_item(doc, &meta.title, *pid));
+            }
+        }
+
+        link_children(doc, entry, &items)?;
+        top.push(entry);
+    }
+    top.extend(leftovers);
+
+    let root = doc.add_object(dictionary! { "Type" => "Outlines" });
+    link_children(doc, root, &top)?;
+    doc.catalog_mut()?.set("Outlines", root);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SectionConfig;
+    use crate::page_meta::PageMeta;
+    use crate::page_tree;
+
+    /// (섹션, 출처, 제목)대로 페이지를 만든 문서. front 섹션은 PageKind::Front, 나머지는 Art
+    fn doc_with_pages(pages: &[(&str, &str, &str)]) -> (Document, Vec<ObjectId>) {
+        let mut doc = Document::with_version("1.7");
+        let root = doc.new_object_id();
+        let ids: Vec<ObjectId> = pages.iter()
+            .map(|_| doc.add_object(dictionary! { "Type" => "Page" }))
+            .collect();
+        page_tree::build_balanced(&mut doc, root, &ids).unwrap();
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => root });
+        doc.trailer.set("Root", catalog);
+        for (pid, (section, source, title)) in ids.iter().zip(pages) {
+            let kind = if *section == "front" { PageKind::Front } else { PageKind::Art };
+            let mut meta = PageMeta::new(kind);
+            meta.section = section.to_string();
+            meta.source = source.to_string();
+            meta.title = title.to_string();
+            page_meta::set(&mut doc, *pid, &meta).unwrap();
+        }
+        (doc, ids)
+    }
+
+    fn title(doc: &Document, id: ObjectId) -> Object {
+        doc.get_object(id).unwrap().as_dict().unwrap().get(b"Title").unwrap().clone()
+    }
+
+    fn titles(doc: &Document, ids: &[ObjectId]) -> Vec<Object> {
+        ids.iter().map(|id| title(doc, *id)).collect()
+    }
+
+    fn count(doc: &Document, id: ObjectId) -> i64 {
+        doc.get_object(id).unwrap().as_dict().unwrap().get(b"Count").and_then(Object::as_i64).unwrap_or(0)
+    }
+
+    #[test]
+    fn text_strings() {
+        assert_eq!(text_string("Chapter 1"), Object::string_literal("Chapter 1"));
+        assert_eq!(
+            text_string("é☃"),
+            Object::String(vec![0xFE, 0xFF, 0x00, 0xE9, 0x26, 0x03], lopdf::StringFormat::Hexadecimal),
+        );
+        // BMP 밖 문자는 서로게이트 쌍
+        let Object::String(bytes, _) = text_string("𝄞") else { panic!() };
+        assert_eq!(bytes, [0xFE, 0xFF, 0xD8, 0x34, 0xDD, 0x1E]);
+    }
+
+    #[test]
+    fn one_entry_per_section_run() {
+        let (mut doc, ids) = doc_with_pages(&[
+            ("front", "", ""), ("body", "a.svg", "A"), ("body", "b.svg", "B"), ("back", "", ""),
+        ]);
+        let mut project = ProjectConfig::default();
+        project.sections.insert(String::from("body"), SectionConfig { title: Some(String::from("Plates")), ..SectionConfig::default() });
+        build_outline(&mut doc, &project).unwrap();
+
+        let top = top_level_items(&doc);
+        assert_eq!(titles(&doc, &top), ["front", "Plates", "back"].map(Object::string_literal));
+        let dest = doc.get_object(top[1]).unwrap().as_dict().unwrap().get(b"Dest").unwrap().as_array().unwrap().clone();
+        assert_eq!(dest[0], Object::Reference(ids[1]));
+        assert!(children(&doc, top[1]).is_empty());
+    }
+
+    #[test]
+    fn page_entries_skip_second_spread_half() {
+        let (mut doc, _) = doc_with_pages(&[
+            ("body", "a.svg", "A"), ("body", "wide.spread.svg", "Wide"), ("body", "wide.spread.svg", "Wide"), ("body", "b.svg", ""),
+        ]);
+        let mut project = ProjectConfig::default();
+        project.outline.pages = true;
+        build_outline(&mut doc, &project).unwrap();
+
+        let top = top_level_items(&doc);
+        let body = children(&doc, top[0]);
+        // 제목 없는 페이지도 건너뜀
+        assert_eq!(titles(&doc, &body), ["A", "Wide"].map(Object::string_literal));
+        assert_eq!(count(&doc, top[0]), 2);
+        let root = outline_root(&doc).unwrap();
+        assert_eq!(count(&doc, root), 3);
+    }
+
+    #[test]
+    fn existing_bookmarks_move_under_their_section() {
+        let (mut doc, ids) = doc_with_pages(&[("front", "", ""), ("front", "", ""), ("body", "a.svg", "A")]);
+        let toc = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Contents"),
+            "Dest" => vec![ids[1].into(), "Fit".into()],
+        });
+        let gone = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Removed page"),
+            "Dest" => vec![(999, 0).into(), "Fit".into()],
+        });
+        append_top_level(&mut doc, &[toc, gone]).unwrap();
+
+        build_outline(&mut doc, &ProjectConfig::default()).unwrap();
+
+        let top = top_level_items(&doc);
+        assert_eq!(titles(&doc, &top), ["front", "body"].map(Object::string_literal));
+        assert_eq!(children(&doc, top[0]), [toc]);
+        assert_eq!(item_ref(&doc, toc, b"Parent"), Some(top[0]));
+        assert_eq!(all_items(&doc).len(), 3);
+    }
+}
@@ -0,0 +1,213 @@
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use crate::process_pages::{Anchor, AxisAnchor, FitMode};
+
+/// 병합 과정에서 각 페이지 딕셔너리에 붙여두는 빌드 메타데이터 키.
+/// append/renumber/삭제를 거쳐도 페이지와 함께 움직이고, 저장 직전에 `strip`으로 제거한다.
+const META_KEY: &[u8] = b"BB_Meta";
+
+/// 페이지 출처
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageKind {
+    Front,
+    Art,
+    Blank,
+    Back,
+}
+
+impl PageKind {
+    fn as_name(&self) -> &'static str {
+        match self {
+            PageKind::Front => "Front",
+            PageKind::Art => "Art",
+            PageKind::Blank => "Blank",
+            PageKind::Back => "Back",
+        }
+    }
+
+    fn from_name(n: &[u8]) -> Option<PageKind> {
+        match n {
+            b"Front" => Some(PageKind::Front),
+            b"Art" => Some(PageKind::Art),
+            b"Blank" => Some(PageKind::Blank),
+            b"Back" => Some(PageKind::Back),
+            _ => None,
+        }
+    }
+}
+
+/// 페이지 단위 설정 (manifest / sidecar 오버라이드 반영 결과)
+#[derive(Debug, Clone)]
+pub struct PageMeta {
+    pub kind: PageKind,
+    /// 소속 섹션 이름 (front / body / back / 사용자 정의)
+    pub section: String,
+    /// 원본 파일명 (SVG 등). 없으면 빈 문자열
+    pub source: String,
+    /// 책갈피(outline)용 제목 (SVG `<title>` 또는 파일명). 없으면 빈 문자열
+    pub title: String,
+    /// `apply_inner_margin` 스케일링 적용 여부
+    pub inner_margin: bool,
+    /// 쪽번호(folio) 허용 여부 (페이지별 설정; 앞부속/빈 페이지/풀블리드 제외 규칙은 `[folio]`에서 따로 적용)
+    pub folio: bool,
+    /// 재단선 밖까지 채우는 페이지 (여백/머리말 대상 아님)
+    pub full_bleed: bool,
+    /// 여백 피팅 시 사용할 모드/피벗 (None이면 기본 정책)
+    pub fit: Option<FitMode>,
+    pub anchor: Option<Anchor>,
+}
+
+impl PageMeta {
+    /// 섹션은 출처 기준 기본값 (front / body / back).
+    /// 빈 페이지 템플릿은 이미 페이지 기준으로 배치되어 있으므로 여백 피팅 대상 아님
+    pub fn new(kind: PageKind) -> PageMeta {
+        let section = match kind {
+            PageKind::Front => "front",
+            PageKind::Art | PageKind::Blank => "body",
+            PageKind::Back => "back",
+        };
+        PageMeta {
+            kind,
+            section: section.to_string(),
+            source: String::new(),
+            title: String::new(),
+            inner_margin: kind != PageKind::Blank,
+            folio: true,
+            full_bleed: false,
+            fit: None,
+            anchor: None,
+        }
+    }
+
+    /// front matter 섹션 소속 여부 (그 구간에 생성된 빈 페이지 포함)
+    pub fn is_front(&self) -> bool {
+        self.section == "front"
+    }
+
+    fn to_dict(&self) -> Dictionary {
+        let mut d = Dictionary::new();
+        d.set("Kind", Object::Name(self.kind.as_name().as_bytes().to_vec()));
+        d.set("Section", Object::string_literal(self.section.as_str()));
+        d.set("Source", Object::string_literal(self.source.as_str()));
+        d.set("Title", Object::string_literal(self.title.as_str()));
+        d.set("InnerMargin", self.inner_margin);
+        d.set("Folio", self.folio);
+        d.set("FullBleed", self.full_bleed);
+        if let Some(m) = self.fit {
+            d.set("Fit", Object::Name(fit_name(m).as_bytes().to_vec()));
+        }
+        if let Some(a) = self.anchor {
+            d.set("Anchor", Object::Array(vec![
+                Object::Name(axis_name(a.x).as_bytes().to_vec()),
+                Object::Name(axis_name(a.y).as_bytes().to_vec()),
+            ]));
+        }
+        d
+    }
+
+    fn from_dict(d: &Dictionary) -> Option<PageMeta> {
+        let kind = PageKind::from_name(d.get(b"Kind").ok()?.as_name().ok()?)?;
+        let mut meta = PageMeta::new(kind);
+        if let Ok(Object::String(s, _)) = d.get(b"Section") {
+            meta.section = String::from_utf8_lossy(s).into_owned();
+        }
+        if let Ok(Object::String(s, _)) = d.get(b"Source") {
+            meta.source = String::from_utf8_lossy(s).into_owned();
+        }
+        if let Ok(Object::String(s, _)) = d.get(b"Title") {
+            meta.title = String::from_utf8_lossy(s).into_owned();
+        }
+        if let Ok(Object::Boolean(b)) = d.get(b"InnerMargin") { meta.inner_margin = *b; }
+        if let Ok(Object::Boolean(b)) = d.get(b"Folio") { meta.folio = *b; }
+        if let Ok(Object::Boolean(b)) = d.get(b"FullBleed") { meta.full_bleed = *b; }
+        if let Ok(Object::Name(n)) = d.get(b"Fit") {
+            meta.fit = fit_from_name(n);
+        }
+        if let Ok(Object::Array(a)) = d.get(b"Anchor")
+            && a.len() == 2
+            && let (Ok(x), Ok(y)) = (a[0].as_name(), a[1].as_name())
+            && let (Some(x), Some(y)) = (axis_from_name(x), axis_from_name(y))
+        {
+            meta.anchor = Some(Anchor { x, y });
+        }
+        Some(meta)
+    }
+}
+
+fn fit_name(m: FitMode) -> &'static str {
+    match m {
+        FitMode::Contain => "Contain",
+        FitMode::Cover => "Cover",
+    }
+}
+
+fn fit_from_name(n: &[u8]) -> Option<FitMode> {
+    match n {
+        b"Contain" => Some(FitMode::Contain),
+        b"Cover" => Some(FitMode::Cover),
+        _ => None,
+    }
+}
+
+fn axis_name(a: AxisAnchor) -> &'static str {
+    match a {
+        AxisAnchor::Start => "Start",
+        AxisAnchor::Center => "Center",
+        AxisAnchor::End => "End",
+    }
+}
+
+fn axis_from_name(n: &[u8]) -> Option<AxisAnchor> {
+    match n {
+        b"Start" => Some(AxisAnchor::Start),
+        b"Center" => Some(AxisAnchor::Center),
+        b"End" => Some(AxisAnchor::End),
+        _ => None,
+    }
+}
+
+/// 문서의 모든 페이지에 같은 메타데이터 부착 (append 전 단일 문서용)
+pub fn tag_all(doc: &mut Document, meta: &PageMeta) -> lopdf::Result<()> {
+    let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    for pid in page_ids {
+        set(doc, pid, meta)?;
+    }
+    Ok(())
+}
+
+/// 외부 PDF(front/back matter)용: 모든 페이지에 같은 메타데이터 + 출처 "파일명 p.N"
+pub fn tag_all_from(doc: &mut Document, meta: &PageMeta, file_name: &str) -> lopdf::Result<()> {
+    let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    for (i, pid) in page_ids.into_iter().enumerate() {
+        let mut meta = meta.clone();
+        meta.source = format!("{file_name} p.{}", i + 1);
+        set(doc, pid, &meta)?;
+    }
+    Ok(())
+}
+
+pub fn set(doc: &mut Document, page_id: ObjectId, meta: &PageMeta) -> lopdf::Result<()> {
+    let pd = doc.get_object_mut(page_id)?.as_dict_mut()?;
+    pd.set(META_KEY, Object::Dictionary(meta.to_dict()));
+    Ok(())
+}
+
+/// 메타데이터가 없는 페이지(외부에서 들어온 문서 등)는 본문 아트로 간주
+pub fn get(doc: &Document, page_id: ObjectId) -> PageMeta {
+    doc.get_object(page_id)
+        .and_then(|o| o.as_dict())
+        .ok()
+        .and_then(|pd| pd.get(META_KEY).ok())
+        .and_then(|o| o.as_dict().ok())
+        .and_then(PageMeta::from_dict)
+        .unwrap_or_else(|| PageMeta::new(PageKind::Art))
+}
+
+/// 저장 직전: 모든 페이지에서 빌드 메타데이터 제거
+pub fn strip(doc: &mut Document) {
+    let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    for pid in page_ids {
+        if let Ok(pd) = doc.get_object_mut(pid).and_then(|o| o.as_dict_mut()) {
+            pd.remove(META_KEY);
+        }
+    }
+}
@@ -0,0 +1,466 @@
+use lopdf::{dictionary, Document, Object, ObjectId};
+use std::collections::HashSet;
+use std::error::Error;
+
+// 책 조립은 `take_pages`/`build_balanced`로 한 번에 하고 바인더가 쓰는 낱장 연산은 `delete_page`뿐이다.
+// 삽입/이동/복제/회전/교체는 호출하는 곳이 생길 때까지 테스트 빌드에서만 컴파일한다
+
+/// 부모 /Pages 노드에서 상속되는 페이지 속성 (PDF 32000 7.7.3.4)
+pub const INHERITABLE: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// 카탈로그의 /Pages 루트
+pub fn root(doc: &Document) -> Result<ObjectId, Box<dyn Error>> {
+    Ok(doc.catalog()?.get(b"Pages")?.as_reference()?)
+}
+
+fn parent_of(doc: &Document, node: ObjectId) -> Option<ObjectId> {
+    doc.get_object(node).ok()?.as_dict().ok()?.get(b"Parent").ok()?.as_reference().ok()
+}
+
+fn kids_of(doc: &Document, node: ObjectId) -> Vec<ObjectId> {
+    doc.get_object(node)
+        .and_then(|o| o.as_dict())
+        .and_then(|d| d.get(b"Kids"))
+        .and_then(|k| doc.dereference(k))
+        .and_then(|(_, k)| k.as_array())
+        .map(|a| a.iter().filter_map(|o| o.as_reference().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// 문서 순서의 페이지 위치 (0부터)
+#[cfg(test)]
+pub fn page_index(doc: &Document, page_id: ObjectId) -> Option<usize> {
+    doc.get_pages().values().position(|pid| *pid == page_id)
+}
+
+/// 페이지 속성 조회: 페이지에 없으면 Parent 사슬을 따라 올라감 (순환 방지)
+pub fn inherited(doc: &Document, page_id: ObjectId, key: &[u8]) -> Option<Object> {
+    let mut seen = HashSet::new();
+    let mut cur = Some(page_id);
+    while let Some(id) = cur {
+        if !seen.insert(id) {
+            break;
+        }
+        let dict = doc.get_object(id).ok()?.as_dict().ok()?;
+        if let Ok(v) = dict.get(key) {
+            return Some(v.clone());
+        }
+        cur = parent_of(doc, id);
+    }
+    None
+}
+
+/// 상속받던 속성을 페이지에 직접 기록 (다른 부모 아래로 옮기기 전에 필요)
+pub fn materialize_inherited(doc: &mut Document, page_id: ObjectId) -> Result<(), Box<dyn Error>> {
+    let found: Vec<(&[u8], Object)> = INHERITABLE.iter()
+        .filter_map(|key| inherited(doc, page_id, key).map(|v| (*key, v)))
+        .collect();
+    let page = doc.get_object_mut(page_id)?.as_dict_mut()?;
+    for (key, value) in found {
+        if !page.has(key) {
+            page.set(key, value);
+        }
+    }
+    Ok(())
+}
+
+/// `node`부터 루트까지 /Count에 `delta` 더함
+fn adjust_counts(doc: &mut Document, node: ObjectId, delta: i64) -> Result<(), Box<dyn Error>> {
+    let mut seen = HashSet::new();
+    let mut cur = Some(node);
+    while let Some(id) = cur {
+        if !seen.insert(id) {
+            break;
+        }
+        let dict = doc.get_object_mut(id)?.as_dict_mut()?;
+        let count = dict.get(b"Count").and_then(Object::as_i64).unwrap_or(0);
+        dict.set("Count", (count + delta).max(0));
+        cur = parent_of(doc, id);
+    }
+    Ok(())
+}
+
+/// 페이지를 트리에서 떼어냄 (객체는 남김). 비게 된 중간 /Pages 노드도 정리
+fn detach(doc: &mut Document, page_id: ObjectId) -> Result<(), Box<dyn Error>> {
+    let parent = parent_of(doc, page_id).ok_or("page has no Parent")?;
+    remove_kid(doc, parent, page_id)?;
+    adjust_counts(doc, parent, -1)?;
+
+    // 빈 중간 노드 제거 (루트는 유지)
+    let root = root(doc)?;
+    let mut node = parent;
+    while node != root && kids_of(doc, node).is_empty() {
+        let Some(up) = parent_of(doc, node) else { break };
+        remove_kid(doc, up, node)?;
+        doc.objects.remove(&node);
+        node = up;
+    }
+    Ok(())
+}
+
+fn remove_kid(doc: &mut Document, parent: ObjectId, kid: ObjectId) -> Result<(), Box<dyn Error>> {
+    let dict = doc.get_object_mut(parent)?.as_dict_mut()?;
+    if let Ok(Object::Array(kids)) = dict.get_mut(b"Kids") {
+        kids.retain(|o| !matches!(o, Object::Reference(id) if *id == kid));
+    }
+    Ok(())
+}
+
+/// 트리 밖의 페이지 객체를 문서 순서 `index` 위치에 끼움 (index == 페이지 수면 맨 끝).
+/// 그 자리에 있던 페이지와 같은 /Pages 노드에 들어가므로 상속 속성은 미리 기록해 둘 것
+#[cfg(test)]
+fn attach(doc: &mut Document, page_id: ObjectId, index: usize) -> Result<(), Box<dyn Error>> {
+    let pages: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    let (parent, slot) = match pages.get(index) {
+        Some(next) => {
+            let parent = parent_of(doc, *next).ok_or("page has no Parent")?;
+            let slot = kids_of(doc, parent).iter().position(|k| k == next).unwrap_or(0);
+            (parent, slot)
+        }
+        None => {
+            if index > pages.len() {
+                return Err(format!("page index {index} out of range (0..={})", pages.len()).into());
+            }
+            let root = root(doc)?;
+            (root, kids_of(doc, root).len())
+        }
+    };
+
+    {
+        let dict = doc.get_object_mut(parent)?.as_dict_mut()?;
+        if !matches!(dict.get(b"Kids"), Ok(Object::Array(_))) {
+            dict.set("Kids", Object::Array(Vec::new()));
+        }
+        let kids = dict.get_mut(b"Kids")?.as_array_mut()?;
+        kids.insert(slot.min(kids.len()), Object::Reference(page_id));
+    }
+    doc.get_object_mut(page_id)?.as_dict_mut()?.set("Parent", parent);
+    adjust_counts(doc, parent, 1)
+}
+
+/// 이미 문서에 있는 (트리에 속하지 않은) 페이지 객체를 `index` 위치에 삽입.
+/// 페이지에 없는 상속 속성은 들어간 자리의 값으로 페이지에 기록
+#[cfg(test)]
+pub fn insert_page(doc: &mut Document, index: usize, page_id: ObjectId) -> Result<(), Box<dyn Error>> {
+    attach(doc, page_id, index)?;
+    materialize_inherited(doc, page_id)
+}
+
+/// 페이지를 트리와 문서에서 제거.
+/// Contents 스트림은 다른 페이지와 공유될 수 있으므로(생성된 빈 페이지) `prune_objects`에 맡김
+pub fn delete_page(doc: &mut Document, page_id: ObjectId) -> Result<(), Box<dyn Error>> {
+    detach(doc, page_id)?;
+    doc.objects.remove(&page_id);
+    Ok(())
+}
+
+/// 페이지를 문서 순서 `index` 위치로 옮김 (index는 옮긴 뒤 기준)
+#[cfg(test)]
+pub fn move_page(doc: &mut Document, page_id: ObjectId, index: usize) -> Result<(), Box<dyn Error>> {
+    materialize_inherited(doc, page_id)?;
+    detach(doc, page_id)?;
+    attach(doc, page_id, index)
+}
+
+/// 페이지 복제본을 `index` 위치에 삽입하고 새 페이지 id를 돌려줌.
+/// 콘텐츠 스트림·리소스는 공유하고, 주석은 새 페이지를 가리키도록 복사
+#[cfg(test)]
+pub fn duplicate_page(doc: &mut Document, page_id: ObjectId, index: usize) -> Result<ObjectId, Box<dyn Error>> {
+    materialize_inherited(doc, page_id)?;
+    let mut dict = doc.get_object(page_id)?.as_dict()?.clone();
+    dict.remove(b"Parent");
+    let annots: Vec<Object> = match dict.get(b"Annots").and_then(|a| doc.dereference(a)) {
+        Ok((_, Object::Array(a))) => a.clone(),
+        _ => Vec::new(),
+    };
+    let new_id = doc.add_object(dict);
+
+    if !annots.is_empty() {
+        let mut copies = Vec::with_capacity(annots.len());
+        for annot in annots {
+            let Ok((_, Object::Dictionary(a))) = doc.dereference(&annot) else { continue };
+            let mut a = a.clone();
+            a.remove(b"Popup");
+            if a.has(b"P") {
+                a.set("P", new_id);
+            }
+            copies.push(Object::Reference(doc.add_object(a)));
+        }
+        doc.get_object_mut(new_id)?.as_dict_mut()?.set("Annots", copies);
+    }
+
+    attach(doc, new_id, index)?;
+    Ok(new_id)
+}
+
+/// 페이지를 `degrees`(90의 배수)만큼 시계 방향으로 더 돌림 (상속된 /Rotate 기준)
+#[cfg(test)]
+pub fn rotate_page(doc: &mut Document, page_id: ObjectId, degrees: i64) -> Result<(), Box<dyn Error>> {
+    if degrees % 90 != 0 {
+        return Err(format!("rotation must be a multiple of 90, got {degrees}").into());
+    }
+    let current = inherited(doc, page_id, b"Rotate").and_then(|r| r.as_i64().ok()).unwrap_or(0);
+    let rotate = (current + degrees).rem_euclid(360);
+    doc.get_object_mut(page_id)?.as_dict_mut()?.set("Rotate", rotate);
+    Ok(())
+}
+
+/// `page_id` 자리에 트리 밖의 페이지 객체 `new_page_id`를 넣음.
+/// 새 페이지에 없는 상속 속성은 기존 페이지가 받던 값으로 직접 기록 (`flatten`과 같은 방식).
+/// 기존 페이지 객체만 제거하고 콘텐츠 스트림은 남김 (새 페이지가 재사용할 수 있으므로)
+#[cfg(test)]
+pub fn replace_page(doc: &mut Document, page_id: ObjectId, new_page_id: ObjectId) -> Result<(), Box<dyn Error>> {
+    let parent = parent_of(doc, page_id).ok_or("page has no Parent")?;
+    {
+        let dict = doc.get_object_mut(parent)?.as_dict_mut()?;
+        let kids = dict.get_mut(b"Kids")?.as_array_mut()?;
+        for kid in kids.iter_mut() {
+            if matches!(kid, Object::Reference(id) if *id == page_id) {
+                *kid = Object::Reference(new_page_id);
+            }
+        }
+    }
+    doc.get_object_mut(new_page_id)?.as_dict_mut()?.set("Parent", parent);
+    materialize_inherited(doc, new_page_id)?;
+    doc.objects.remove(&page_id);
+    Ok(())
+}
+
+/// 한 /Pages 노드의 최대 자식 수 (`build_balanced`)
+const FANOUT: usize = 32;
+
+/// 모든 페이지의 상속 속성을 고정한 뒤 /Pages 노드를 전부 제거하고 페이지 id를 문서 순서로 돌려줌.
+/// 페이지들은 트리 밖 객체가 되므로 `build_balanced`로 다시 묶어야 함
+pub fn take_pages(doc: &mut Document) -> Result<Vec<ObjectId>, Box<dyn Error>> {
+    let pages: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    let mut nodes = HashSet::new();
+    for pid in &pages {
+        materialize_inherited(doc, *pid)?;
+        let mut cur = parent_of(doc, *pid);
+        while let Some(node) = cur {
+            if !nodes.insert(node) {
+                break;
+            }
+            cur = parent_of(doc, node);
+        }
+    }
+    if let Ok(root) = root(doc) {
+        nodes.insert(root);
+    }
+    for node in nodes {
+        doc.objects.remove(&node);
+    }
+    for pid in &pages {
+        doc.get_object_mut(*pid)?.as_dict_mut()?.remove(b"Parent");
+    }
+    Ok(pages)
+}
+
+/// 상속 속성을 모든 페이지에 직접 기록하고 트리를 루트 하나 아래로 평평하게 재구성.
+/// 이후 단계는 페이지에 있는 값만 읽으면 됨
+pub fn flatten(doc: &mut Document) -> Result<(), Box<dyn Error>> {
+    let root = root(doc)?;
+    let pages: Vec<(ObjectId, i64)> = take_pages(doc)?.into_iter().map(|pid| (pid, 1)).collect();
+    pages_node(doc, root, &pages)?;
+    Ok(())
+}
+
+/// 트리 밖 페이지들을 노드당 최대 32개씩 묶은 균형 /Pages 트리로 구성 (페이지 수에 선형).
+/// 최상위 노드는 `root_id`에 기록하므로 카탈로그의 /Pages 참조는 그대로 유효
+pub fn build_balanced(doc: &mut Document, root_id: ObjectId, pages: &[ObjectId]) -> Result<(), Box<dyn Error>> {
+    // (노드 id, 하위 페이지 수)
+    let mut level: Vec<(ObjectId, i64)> = pages.iter().map(|pid| (*pid, 1)).collect();
+    while level.len() > FANOUT {
+        let mut next = Vec::with_capacity(level.len().div_ceil(FANOUT));
+        for group in level.chunks(FANOUT) {
+            let node = doc.new_object_id();
+            next.push((node, pages_node(doc, node, group)?));
+        }
+        level = next;
+    }
+    pages_node(doc, root_id, &level)?;
+    Ok(())
+}
+
+/// `id`에 /Pages 노드를 만들고 자식들의 Parent를 연결. 하위 페이지 수를 돌려줌
+fn pages_node(doc: &mut Document, id: ObjectId, kids: &[(ObjectId, i64)]) -> Result<i64, Box<dyn Error>> {
+    for (kid, _) in kids {
+        doc.get_object_mut(*kid)?.as_dict_mut()?.set("Parent", id);
+    }
+    let count: i64 = kids.iter().map(|(_, c)| c).sum();
+    doc.objects.insert(id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Kids" => kids.iter().map(|(k, _)| Object::Reference(*k)).collect::<Vec<_>>(),
+        "Count" => count,
+    }));
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 40쪽짜리 2단 트리 (루트 → 32쪽 노드 + 8쪽 노드).
+    /// 첫 노드에는 MediaBox/Rotate/Resources를 두고, 루트에는 다른 MediaBox를 둠
+    fn two_level_doc() -> (Document, Vec<ObjectId>) {
+        let mut doc = Document::with_version("1.7");
+        let root = doc.new_object_id();
+        let pages: Vec<ObjectId> = (0..40)
+            .map(|i| doc.add_object(dictionary! { "Type" => "Page", "Contents" => Object::string_literal(format!("p{i}")) }))
+            .collect();
+        build_balanced(&mut doc, root, &pages).unwrap();
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => root });
+        doc.trailer.set("Root", catalog);
+
+        doc.get_object_mut(root).unwrap().as_dict_mut().unwrap()
+            .set("MediaBox", vec![0.into(), 0.into(), 500.into(), 500.into()]);
+        let first = kids_of(&doc, root)[0];
+        let node = doc.get_object_mut(first).unwrap().as_dict_mut().unwrap();
+        node.set("MediaBox", vec![0.into(), 0.into(), 300.into(), 400.into()]);
+        node.set("Rotate", 90);
+        node.set("Resources", dictionary! { "Marker" => "first" });
+        (doc, pages)
+    }
+
+    fn order(doc: &Document) -> Vec<ObjectId> {
+        doc.get_pages().values().cloned().collect()
+    }
+
+    fn media_width(doc: &Document, pid: ObjectId) -> i64 {
+        inherited(doc, pid, b"MediaBox").unwrap().as_array().unwrap()[2].as_i64().unwrap()
+    }
+
+    /// 모든 /Pages 노드의 Count가 하위 페이지 수와 같고, 자식의 Parent가 그 노드인지 확인
+    fn assert_consistent(doc: &Document) {
+        fn check(doc: &Document, node: ObjectId) -> i64 {
+            let mut total = 0;
+            for kid in kids_of(doc, node) {
+                assert_eq!(parent_of(doc, kid), Some(node), "Parent of {kid:?}");
+                let dict = doc.get_object(kid).unwrap().as_dict().unwrap();
+                total += match dict.get(b"Type").and_then(Object::as_name) {
+                    Ok(b"Pages") => check(doc, kid),
+                    _ => 1,
+                };
+            }
+            let count = doc.get_object(node).unwrap().as_dict().unwrap()
+                .get(b"Count").and_then(Object::as_i64).unwrap();
+            assert_eq!(count, total, "Count of {node:?}");
+            total
+        }
+        let root = root(doc).unwrap();
+        assert_eq!(check(doc, root) as usize, doc.get_pages().len());
+    }
+
+    #[test]
+    fn builds_two_levels() {
+        let (doc, pages) = two_level_doc();
+        assert_consistent(&doc);
+        assert_eq!(kids_of(&doc, root(&doc).unwrap()).len(), 2);
+        assert_eq!(order(&doc), pages);
+        assert_eq!(page_index(&doc, pages[35]), Some(35));
+    }
+
+    #[test]
+    fn insert_into_second_node() {
+        let (mut doc, pages) = two_level_doc();
+        let new = doc.add_object(dictionary! { "Type" => "Page" });
+        insert_page(&mut doc, 32, new).unwrap();
+        assert_consistent(&doc);
+        assert_eq!(page_index(&doc, new), Some(32));
+        assert_eq!(page_index(&doc, pages[32]), Some(33));
+        assert_eq!(parent_of(&doc, new), parent_of(&doc, pages[32]));
+        // 들어간 자리(루트)의 MediaBox가 페이지에 기록됨
+        assert!(doc.get_object(new).unwrap().as_dict().unwrap().has(b"MediaBox"));
+        assert_eq!(media_width(&doc, new), 500);
+
+        let end = doc.add_object(dictionary! { "Type" => "Page" });
+        insert_page(&mut doc, 41, end).unwrap();
+        assert_consistent(&doc);
+        assert_eq!(page_index(&doc, end), Some(41));
+        assert!(insert_page(&mut doc, 99, new).is_err());
+    }
+
+    #[test]
+    fn delete_removes_empty_node() {
+        let (mut doc, pages) = two_level_doc();
+        for pid in &pages[32..] {
+            delete_page(&mut doc, *pid).unwrap();
+        }
+        assert_consistent(&doc);
+        assert_eq!(order(&doc), pages[..32]);
+        assert_eq!(kids_of(&doc, root(&doc).unwrap()).len(), 1);
+    }
+
+    #[test]
+    fn move_keeps_inherited_attributes() {
+        let (mut doc, pages) = two_level_doc();
+        move_page(&mut doc, pages[0], 39).unwrap();
+        assert_consistent(&doc);
+        assert_eq!(order(&doc).last(), Some(&pages[0]));
+        assert_eq!(order(&doc).first(), Some(&pages[1]));
+        // 첫 노드에서 받던 값이 옮긴 뒤에도 유지
+        assert_eq!(media_width(&doc, pages[0]), 300);
+        assert_eq!(inherited(&doc, pages[0], b"Rotate").and_then(|r| r.as_i64().ok()), Some(90));
+        // 두 번째 노드의 페이지는 그대로
+        assert_eq!(media_width(&doc, pages[39]), 500);
+    }
+
+    #[test]
+    fn duplicate_copies_annotations() {
+        let (mut doc, pages) = two_level_doc();
+        let annot = doc.add_object(dictionary! { "Type" => "Annot", "Subtype" => "Link", "P" => pages[3] });
+        doc.get_object_mut(pages[3]).unwrap().as_dict_mut().unwrap()
+            .set("Annots", vec![Object::Reference(annot)]);
+
+        let copy = duplicate_page(&mut doc, pages[3], 36).unwrap();
+        assert_consistent(&doc);
+        assert_eq!(doc.get_pages().len(), 41);
+        assert_eq!(page_index(&doc, copy), Some(36));
+        assert_eq!(media_width(&doc, copy), 300);
+
+        let annots = doc.get_object(copy).unwrap().as_dict().unwrap().get(b"Annots").unwrap().as_array().unwrap().clone();
+        let copied = annots[0].as_reference().unwrap();
+        assert_ne!(copied, annot);
+        let p = doc.get_object(copied).unwrap().as_dict().unwrap().get(b"P").unwrap().as_reference().unwrap();
+        assert_eq!(p, copy);
+    }
+
+    #[test]
+    fn rotate_adds_to_inherited() {
+        let (mut doc, pages) = two_level_doc();
+        rotate_page(&mut doc, pages[0], 90).unwrap();
+        rotate_page(&mut doc, pages[35], -90).unwrap();
+        let rotate = |doc: &Document, pid| inherited(doc, pid, b"Rotate").and_then(|r| r.as_i64().ok());
+        assert_eq!(rotate(&doc, pages[0]), Some(180));
+        assert_eq!(rotate(&doc, pages[1]), Some(90));
+        assert_eq!(rotate(&doc, pages[35]), Some(270));
+        assert!(rotate_page(&mut doc, pages[0], 45).is_err());
+    }
+
+    #[test]
+    fn replace_materializes_inherited() {
+        let (mut doc, pages) = two_level_doc();
+        let new = doc.add_object(dictionary! { "Type" => "Page", "Rotate" => 0 });
+        replace_page(&mut doc, pages[5], new).unwrap();
+        assert_consistent(&doc);
+        assert_eq!(page_index(&doc, new), Some(5));
+        assert!(doc.get_object(pages[5]).is_err());
+
+        let dict = doc.get_object(new).unwrap().as_dict().unwrap();
+        assert_eq!(dict.get(b"MediaBox").unwrap().as_array().unwrap()[2].as_i64().unwrap(), 300);
+        assert!(dict.has(b"Resources"));
+        // 새 페이지가 가진 값은 덮어쓰지 않음
+        assert_eq!(dict.get(b"Rotate").unwrap().as_i64().unwrap(), 0);
+    }
+
+    #[test]
+    fn flatten_keeps_effective_attributes() {
+        let (mut doc, pages) = two_level_doc();
+        flatten(&mut doc).unwrap();
+        assert_consistent(&doc);
+        assert_eq!(order(&doc), pages);
+        assert_eq!(kids_of(&doc, root(&doc).unwrap()).len(), 40);
+        assert_eq!(media_width(&doc, pages[0]), 300);
+        assert_eq!(media_width(&doc, pages[39]), 500);
+    }
+}
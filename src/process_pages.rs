@@ -1,16 +1,19 @@
 use lopdf::{Document, Object, ObjectId, Stream, Dictionary};
-use lopdf::content::Content;
 use std::error::Error;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use crate::binding_params::Book;
+use crate::config::{MatterResize, ProjectConfig};
+use crate::ink;
+use crate::links;
+use crate::stamp::{self, Bands};
+use crate::page_meta::{self, PageKind};
+use crate::page_tree;
 
 // ========== small helpers ==========
 #[inline]
-fn dict_get<'a>(dict: &'a Dictionary, key: &[u8]) -> Option<&'a Object> {
-    dict.get(key).ok()
-}
-
-#[inline]
-fn obj_as_dict_owned(obj: &Object, doc: &Document) -> Option<Dictionary> {
+pub fn obj_as_dict_owned(obj: &Object, doc: &Document) -> Option<Dictionary> {
     match obj {
         Object::Dictionary(d) => Some(d.clone()),
         Object::Reference(idref) => {
@@ -22,100 +25,101 @@ fn obj_as_dict_owned(obj: &Object, doc: &Document) -> Option<Dictionary> {
 }
 
 // ========== public entry ==========
-pub fn remove_blank_pages(doc: &mut Document) -> Result<(), Box<dyn Error>> {
+/// 빈 페이지로 판정된 이유
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlankReason {
+    /// 빌드가 넣은 빈 페이지 (섹션 시작 맞춤, 단면 인쇄용 등)
+    Generated,
+    /// 페이지 박스 안에 그려진 것이 없음
+    Empty,
+    /// 흰색 / 완전 투명 / 보이지 않는 텍스트만 있음
+    Invisible,
+    /// 흰색 또는 완전 투명한 이미지만 있음 (`[blanks] inspect_images`)
+    BlankImages,
+}
+
+impl std::fmt::Display for BlankReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BlankReason::Generated => "generated blank page",
+            BlankReason::Empty => "nothing drawn inside the page box",
+            BlankReason::Invisible => "only white, transparent or hidden marks",
+            BlankReason::BlankImages => "only white or fully transparent images",
+        })
+    }
+}
 
+/// 빈 페이지 제거 보고 항목
+#[derive(Debug, Clone)]
+pub struct RemovedPage {
+    /// 제거 전 문서에서의 위치 (1부터)
+    pub index: usize,
+    /// 원본 (SVG 파일명, front/back matter 쪽). 생성된 빈 페이지는 빈 문자열
+    pub source: String,
+    pub section: String,
+    pub reason: BlankReason,
+}
+
+/// 빈 페이지를 찾아 제거하고 보고서를 돌려줌 (`dry_run`이면 찾기만 함).
+/// `image_tolerance`가 있으면 이미지도 디코딩해 흰색/투명 이미지만 있는 페이지를 빈 페이지로 봄
+pub fn remove_blank_pages(
+    doc: &mut Document,
+    image_tolerance: Option<f64>,
+    dry_run: bool,
+) -> Result<Vec<RemovedPage>, Box<dyn Error>> {
     let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
-    for pid in page_ids.into_iter().rev() {
-        if page_is_blank(doc, pid)? {
-            delete_page(doc, pid)?; // 정확 삭제
+    let mut report = Vec::new();
+    let mut blank_ids = Vec::new();
+    for (i, pid) in page_ids.iter().enumerate() {
+        if let Some(reason) = blank_reason(doc, *pid, image_tolerance) {
+            let meta = page_meta::get(doc, *pid);
+            report.push(RemovedPage { index: i + 1, source: meta.source, section: meta.section, reason });
+            blank_ids.push(*pid);
         }
     }
+    if dry_run {
+        return Ok(report);
+    }
+
+    for pid in blank_ids.into_iter().rev() {
+        page_tree::delete_page(doc, pid)?; // 정확 삭제
+    }
 
     doc.renumber_objects();
 
     // (있으면) 고아 객체 제거 -> 삭제된 페이지에서만 쓰이던 폰트/이미지도 제거됨
     let _ = doc.prune_objects();   // lopdf 0.38에 있으면 사용, 없으면 생략
 
-    Ok(())
+    Ok(report)
 }
 
 // ========== blank detection ==========
-fn page_is_blank(doc: &mut Document, page_id: ObjectId) -> lopdf::Result<bool> {
-    let streams = page_content_streams(doc, page_id)?;
-    if streams.is_empty() {
-        return Ok(true);
+/// 빈 페이지면 그 이유. 보이는 잉크가 없으면 빈 페이지로 봄
+/// (흰색/투명 칠, 보이지 않는 텍스트, 페이지 밖으로 잘린 마크는 무시)
+fn blank_reason(doc: &Document, page_id: ObjectId, image_tolerance: Option<f64>) -> Option<BlankReason> {
+    // 생성된 빈 페이지는 템플릿(안내문/격자 등)이 그려져 있어도 제거
+    if page_meta::get(doc, page_id).kind == PageKind::Blank {
+        return Some(BlankReason::Generated);
     }
-
-    let resources = effective_resources(doc, page_id);
-
-    for s in streams {
-        let content = Content::decode(&s.content)?;
-        if draws_something(doc, &content, &resources)? {
-            return Ok(false);
-        }
+    // 한 번 해석한 결과에서 이유까지 가림 (이미지도 한 번만 디코딩)
+    let ink = ink::page_ink(doc, page_id, image_tolerance);
+    if ink.visible.is_some() {
+        return None;
     }
-    Ok(true)
-}
-
-fn draws_something(doc: &Document, content: &Content, resources: &Option<Dictionary>) -> lopdf::Result<bool> {
-    for op in &content.operations {
-        let name = op.operator.as_str();
-
-        // 텍스트/경로/셰이딩/인라인 이미지
-        if matches!(name, "Tj" | "TJ" | "'" | "\"" |
-                          "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" |
-                          "sh" | "BI")
-        {
-            return Ok(true);
-        }
-
-        // XObject 호출 처리
-        if name == "Do" {
-            if let Some(first) = op.operands.get(0) {
-                if let Some(res) = resources {
-                    if let Some(xobjs_obj) = dict_get(res, b"XObject") {
-                        let xdict = obj_as_dict_owned(xobjs_obj, doc).unwrap_or_else(Dictionary::new);
-
-                        if let Object::Name(nm) = first {
-                            if let Some(obj) = dict_get(&xdict, nm.as_slice()) {
-                                if let Object::Reference(oid) = obj {
-                                    let xobj = doc.get_object(*oid)?.as_stream()?;
-                                    if let Some(sub_obj) = xobj.dict.get(b"Subtype").ok() {
-                                        if let Object::Name(sub) = sub_obj {
-                                            match sub.as_slice() {
-                                                b"Image" => return Ok(true),
-                                                b"Form"  => {
-                                                    let inner = Content::decode(&xobj.content)?;
-                                                    // Form 전용 Resources 우선
-                                                    let frm_res = if let Some(r) = xobj.dict.get(b"Resources").ok() {
-                                                        obj_as_dict_owned(r, doc)
-                                                    } else {
-                                                        resources.clone()
-                                                    };
-                                                    if draws_something(doc, &inner, &frm_res)? {
-                                                        return Ok(true);
-                                                    }
-                                                }
-                                                _ => {}
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    if ink.raw.is_none() {
+        return Some(BlankReason::Empty);
+    }
+    if ink.visible_with_images.is_some() {
+        return Some(BlankReason::BlankImages);
     }
-    Ok(false)
+    Some(BlankReason::Invisible)
 }
 
-fn page_content_streams(doc: &Document, page_id: ObjectId) -> lopdf::Result<Vec<Stream>> {
+pub fn page_content_streams(doc: &Document, page_id: ObjectId) -> lopdf::Result<Vec<Stream>> {
     let page = doc.get_object(page_id)?.as_dict()?;
     let mut out = Vec::new();
 
-    if let Some(obj) = page.get(b"Contents").ok() {
+    if let Ok(obj) = page.get(b"Contents") {
         match obj {
             Object::Reference(cid) => {
                 out.push(doc.get_object(*cid)?.as_stream()?.clone());
@@ -134,102 +138,28 @@ fn page_content_streams(doc: &Document, page_id: ObjectId) -> lopdf::Result<Vec<
     Ok(out)
 }
 
-fn effective_resources(doc: &Document, page_id: ObjectId) -> Option<Dictionary> {
-    // 페이지에 직접 있으면 사용
-    let page = doc.get_object(page_id).ok()?.as_dict().ok()?;
-    if let Some(obj) = page.get(b"Resources").ok() {
-        return obj_as_dict_owned(obj, doc);
-    }
-
-    // Parent 사슬 따라 상속 탐색
-    let mut cur = page;
-    loop {
-        match dict_get(cur, b"Parent") {
-            Some(Object::Reference(pid)) => {
-                let parent = doc.get_object(*pid).ok()?.as_dict().ok()?;
-                if let Some(obj) = parent.get(b"Resources").ok() {
-                    return obj_as_dict_owned(obj, doc);
-                }
-                cur = parent;
-            }
-            _ => break,
-        }
-    }
-    None
+pub fn effective_resources(doc: &Document, page_id: ObjectId) -> Option<Dictionary> {
+    // 페이지에 없으면 Parent 사슬 따라 상속 탐색
+    obj_as_dict_owned(&page_tree::inherited(doc, page_id, b"Resources")?, doc)
 }
 
-// ========== deletion ==========
-fn delete_page(doc: &mut Document, page_id: ObjectId) -> Result<(), Box<dyn Error>> {
-    // 1) Parent
-    let parent_id = {
-        let page_dict = doc.get_object(page_id)?.as_dict()?;
-        match page_dict.get(b"Parent")? {
-            Object::Reference(pid) => *pid,
-            _ => return Err("page has no Parent".into()),
-        }
-    };
-
-    // 2) Parent.Kids에서 제거
-    {
-        let parent = doc.get_object_mut(parent_id)?;
-        let pdict = parent.as_dict_mut()?;
-        if let Ok(obj) = pdict.get_mut(b"Kids") {
-            if let Object::Array(kids) = obj {
-                kids.retain(|o| !matches!(o, Object::Reference(id) if *id == page_id));
-            }
-        }
-    }
-
-    // 3) 조상 /Count 감소
-    let mut cur = Some(parent_id);
-    while let Some(pid) = cur {
-        let pobj = doc.get_object_mut(pid)?;
-        let pdict = pobj.as_dict_mut()?;
-
-        if let Ok(obj) = pdict.get_mut(b"Count") {
-            if let Object::Integer(c) = obj { // c: &mut i64 (match ergonomics)
-                *c -= 1;
-            }
-        }
-
-        cur = match pdict.get(b"Parent") {
-            Ok(Object::Reference(pp)) => Some(*pp),
-            _ => None,
-        };
-    }
-
-    // 4) 삭제할 Contents 참조들 먼저 수집 (borrow 충돌 방지)
-    let content_ids: Vec<ObjectId> = {
-        let pd = doc.get_object(page_id)?.as_dict()?;
-        let mut ids = Vec::new();
-        if let Some(obj) = pd.get(b"Contents").ok() {
-            match obj {
-                Object::Reference(cid) => ids.push(*cid),
-                Object::Array(arr) => {
-                    for o in arr {
-                        if let Object::Reference(cid) = o {
-                            ids.push(*cid);
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-        ids
-    };
-
-    // 5) Contents 스트림 제거
-    for cid in content_ids {
-        doc.objects.remove(&cid);
-    }
-
-    // 6) 페이지 객체 제거
-    doc.objects.remove(&page_id);
-
-    Ok(())
+// Helvetica / Helvetica-Bold (WinAnsi, 32..126) widths in 1/1000 em
+/// Helvetica 기준 문자열 폭 (pt). ASCII 밖 문자는 600/1000em으로 근사
+pub fn helvetica_text_width(s: &str, fs: f64) -> f64 {
+    const HELV_W_32_126: [i16; 95] = [
+        278,278,355,556,556,889,667,191,333,333,389,584,278,333,278,278,
+        556,556,556,556,556,556,556,556,556,556,278,278,584,584,584,556,
+        1015,667,667,722,722,667,611,778,722,278,500,667,556,833,722,778,
+        667,778,722,667,611,722,667,944,667,667,611,278,278,278,469,556,
+        333,556,556,500,556,556,278,556,556,222,222,500,222,833,556,556,
+        556,556,333,500,278,556,500,722,500,500,500,334,260,334,584,
+    ];
+    let w1000: f64 = s.chars().map(|c|
+        if (' '..='~').contains(&c) { HELV_W_32_126[(c as usize) - 32] as f64 } else { 600.0 }
+    ).sum();
+    w1000 * fs / 1000.0
 }
 
-// Helvetica / Helvetica-Bold (WinAnsi, 32..126) widths in 1/1000 em
 pub fn stamp_watermarks(doc: &mut Document) -> Result<(), Box<dyn Error>> {
     // 1) 공유 리소스: Helvetica-Bold / 반투명 GState
     let font_id = {
@@ -252,22 +182,6 @@ pub fn stamp_watermarks(doc: &mut Document) -> Result<(), Box<dyn Error>> {
         id
     };
 
-    // 헬베티카 폭표(중앙정렬용)
-    const HELV_W_32_126: [i16; 95] = [
-        278,278,355,556,556,889,667,191,333,333,389,584,278,333,278,278,
-        556,556,556,556,556,556,556,556,556,556,278,278,584,584,584,556,
-        1015,667,667,722,722,667,611,778,722,278,500,667,556,833,722,778,
-        667,778,722,667,611,722,667,944,667,667,611,278,278,278,469,556,
-        333,556,556,500,556,556,278,556,556,222,222,500,222,833,556,556,
-        556,556,333,500,278,556,500,722,500,500,500,334,260,334,584,
-    ];
-    let text_width = |s: &str, fs: f64| -> f64 {
-        let w1000: f64 = s.bytes().map(|b|
-            if (32..=126).contains(&b) { HELV_W_32_126[(b-32) as usize] as f64 } else { 600.0 }
-        ).sum();
-        w1000 * fs / 1000.0
-    };
-
     let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
     for pid in page_ids {
         // --- 페이지 박스/중앙 ---
@@ -303,27 +217,22 @@ pub fn stamp_watermarks(doc: &mut Document) -> Result<(), Box<dyn Error>> {
             xobj_name_for_old = Some(b"OLD_FORM".to_vec());
 
             // 4) 페이지 리소스 사본 만들고 /XObject에 OLD_FORM 추가(+ 우리 폰트/GS)
-            let mut resources = {
-                let page_ro = doc.get_object(pid)?.as_dict()?.clone();
-                if let Some(obj) = page_ro.get(b"Resources").ok() {
-                    obj_as_dict_owned(obj, doc).unwrap_or_else(Dictionary::new)
-                } else { Dictionary::new() }
-            };
+            let mut resources = effective_resources(doc, pid).unwrap_or_default();
             // /XObject
-            let mut xobjs = if let Some(o) = resources.get(b"XObject").ok() {
-                obj_as_dict_owned(o, doc).unwrap_or_else(Dictionary::new)
+            let mut xobjs = if let Ok(o) = resources.get(b"XObject") {
+                obj_as_dict_owned(o, doc).unwrap_or_default()
             } else { Dictionary::new() };
             xobjs.set("OLD_FORM", Object::Reference(form_id));
             resources.set("XObject", Object::Dictionary(xobjs));
             // /Font
-            let mut fr = if let Some(o) = resources.get(b"Font").ok() {
-                obj_as_dict_owned(o, doc).unwrap_or_else(Dictionary::new)
+            let mut fr = if let Ok(o) = resources.get(b"Font") {
+                obj_as_dict_owned(o, doc).unwrap_or_default()
             } else { Dictionary::new() };
             fr.set("F_ARC", Object::Reference(font_id));
             resources.set("Font", Object::Dictionary(fr));
             // /ExtGState
-            let mut gs = if let Some(o) = resources.get(b"ExtGState").ok() {
-                obj_as_dict_owned(o, doc).unwrap_or_else(Dictionary::new)
+            let mut gs = if let Ok(o) = resources.get(b"ExtGState") {
+                obj_as_dict_owned(o, doc).unwrap_or_default()
             } else { Dictionary::new() };
             gs.set("GS_ARC", Object::Reference(gs_id));
             resources.set("ExtGState", Object::Dictionary(gs));
@@ -336,19 +245,14 @@ pub fn stamp_watermarks(doc: &mut Document) -> Result<(), Box<dyn Error>> {
             }
         } else {
             // 기존 리소스가 없어도 워터마크용 Font/GS는 필요
-            let mut resources = {
-                let page_ro = doc.get_object(pid)?.as_dict()?.clone();
-                if let Some(obj) = page_ro.get(b"Resources").ok() {
-                    obj_as_dict_owned(obj, doc).unwrap_or_else(Dictionary::new)
-                } else { Dictionary::new() }
-            };
-            let mut fr = if let Some(o) = resources.get(b"Font").ok() {
-                obj_as_dict_owned(o, doc).unwrap_or_else(Dictionary::new)
+            let mut resources = effective_resources(doc, pid).unwrap_or_default();
+            let mut fr = if let Ok(o) = resources.get(b"Font") {
+                obj_as_dict_owned(o, doc).unwrap_or_default()
             } else { Dictionary::new() };
             fr.set("F_ARC", Object::Reference(font_id));
             resources.set("Font", Object::Dictionary(fr));
-            let mut gs = if let Some(o) = resources.get(b"ExtGState").ok() {
-                obj_as_dict_owned(o, doc).unwrap_or_else(Dictionary::new)
+            let mut gs = if let Ok(o) = resources.get(b"ExtGState") {
+                obj_as_dict_owned(o, doc).unwrap_or_default()
             } else { Dictionary::new() };
             gs.set("GS_ARC", Object::Reference(gs_id));
             resources.set("ExtGState", Object::Dictionary(gs));
@@ -362,7 +266,7 @@ pub fn stamp_watermarks(doc: &mut Document) -> Result<(), Box<dyn Error>> {
         // --- 워터마크 텍스트(중앙정렬 + 진짜/가짜 볼드 + 밑줄) ---
         let text = "ARC";
         let fs = 0.25 * w.min(h);
-        let tw = text_width(text, fs);
+        let tw = helvetica_text_width(text, fs);
         let theta = 45f64.to_radians(); let (c,s) = (theta.cos(), theta.sin()); let ms = -s;
         let dx = -tw/2.0;          // 정확 중앙 정렬
         let dy = -(fs*0.35);
@@ -417,7 +321,7 @@ pub fn stamp_watermarks(doc: &mut Document) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn as_f64(n: &Object) -> Option<f64> {
+pub fn as_f64(n: &Object) -> Option<f64> {
     match n {
         Object::Integer(i) => Some(*i as f64),
         Object::Real(r) => Some(*r as f64),
@@ -427,34 +331,70 @@ fn as_f64(n: &Object) -> Option<f64> {
 
 fn effective_mediabox(doc: &Document, page_id: ObjectId) -> Option<(f64, f64, f64, f64)> {
     // 페이지에서 시작해 Parent 체인을 올라가며 /MediaBox 탐색
-    let mut cur = doc.get_object(page_id).ok()?.as_dict().ok()?;
-    loop {
-        if let Some(obj) = cur.get(b"MediaBox").ok() {
-            if let Object::Array(a) = obj {
-                if a.len() == 4 {
-                    let llx = as_f64(&a[0])?;
-                    let lly = as_f64(&a[1])?;
-                    let urx = as_f64(&a[2])?;
-                    let ury = as_f64(&a[3])?;
-                    return Some((llx, lly, urx, ury));
-                }
-            }
-        }
-        match dict_get(cur, b"Parent") {
-            Some(Object::Reference(pid)) => {
-                cur = doc.get_object(*pid).ok()?.as_dict().ok()?;
-            }
-            _ => break,
+    let mb = page_tree::inherited(doc, page_id, b"MediaBox")?;
+    let a = doc.dereference(&mb).ok()?.1.as_array().ok()?;
+    if a.len() != 4 {
+        return None;
+    }
+    Some((as_f64(&a[0])?, as_f64(&a[1])?, as_f64(&a[2])?, as_f64(&a[3])?))
+}
+
+/// 축 방향 정렬 기준 (x: Start=왼쪽, y: Start=아래쪽 — PDF 좌표계)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AxisAnchor { Start, Center, End }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum FitMode { Contain, Cover }
+
+/// 2축 정렬 기준 (피벗)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub x: AxisAnchor,
+    pub y: AxisAnchor,
+}
+
+/// `apply_inner_margin` 피팅 정책
+#[derive(Debug, Clone, Copy)]
+pub struct FitPolicy {
+    /// 잉크 면적 / 세이프 면적 이 값 미만이면 희소 페이지
+    pub sparse_threshold: f64,
+    pub sparse_anchor: Anchor,
+    pub dense_anchor: Anchor,
+    /// 스케일 상한 (1.0 = 업스케일 금지, INFINITY = 제한 없음)
+    pub sparse_max_scale: f64,
+    pub dense_max_scale: f64,
+    pub mode: FitMode,
+}
+
+impl Default for FitPolicy {
+    fn default() -> Self {
+        Self {
+            sparse_threshold: 0.12,
+            // 바닥 중앙(anchor: Center×Bottom), 업스케일 방지
+            sparse_anchor: Anchor { x: AxisAnchor::Center, y: AxisAnchor::Start },
+            // 일반은 중앙(anchor: Center×Center), 제한 없음(다운스케일은 자연스럽게 됨)
+            dense_anchor: Anchor { x: AxisAnchor::Center, y: AxisAnchor::Center },
+            sparse_max_scale: 1.0,
+            dense_max_scale: f64::INFINITY,
+            mode: FitMode::Contain,
         }
     }
-    None
 }
 
-#[derive(Clone, Copy)]
-enum AxisAnchor { Start, Center, End }
+/// 섹션별 피팅 정책 (없는 섹션은 기본 정책)
+#[derive(Debug, Clone, Default)]
+pub struct FitPolicies {
+    pub default: FitPolicy,
+    pub sections: HashMap<String, FitPolicy>,
+}
 
-#[derive(Clone, Copy)]
-enum FitMode { Contain, Cover }
+impl FitPolicies {
+    pub fn for_section(&self, section: &str) -> &FitPolicy {
+        self.sections.get(section).unwrap_or(&self.default)
+    }
+}
 
 #[inline]
 fn anchor_value(start: f64, end: f64, a: AxisAnchor) -> f64 {
@@ -465,10 +405,15 @@ fn anchor_value(start: f64, end: f64, a: AxisAnchor) -> f64 {
     }
 }
 
+/// 폭이나 높이가 사실상 0인 박스 (배율을 정할 수 없음)
+pub fn is_degenerate((x0, y0, x1, y1): (f64, f64, f64, f64)) -> bool {
+    x1 - x0 < 1e-6 || y1 - y0 < 1e-6
+}
+
 /// U(콘텐츠 AABB) → S(세이프 AABB)로 등방 스케일 + 피벗 정렬
-fn fit_with_anchor(
-    ux0: f64, uy0: f64, ux1: f64, uy1: f64,
-    sx0: f64, sy0: f64, sx1: f64, sy1: f64,
+pub fn fit_with_anchor(
+    (ux0, uy0, ux1, uy1): (f64, f64, f64, f64),
+    (sx0, sy0, sx1, sy1): (f64, f64, f64, f64),
     ax: AxisAnchor, ay: AxisAnchor,
     mode: FitMode, s_max: f64, // 희소면 1.0, 일반은 f64::INFINITY 권장
 ) -> (f64, f64, f64) {
@@ -478,7 +423,11 @@ fn fit_with_anchor(
         FitMode::Contain => (sw / uw).min(sh / uh),
         FitMode::Cover   => (sw / uw).max(sh / uh),
     };
-    let s = s0.min(s_max);
+    // 폭이나 높이가 0인 U(선 하나, 점)는 호출 측에서 걸러야 함. 여기서는 inf/NaN만 막음
+    let s = match s0.min(s_max) {
+        s if s.is_finite() && s > 0.0 => s,
+        _ => 1.0,
+    };
 
     let u_px = anchor_value(ux0, ux1, ax);
     let u_py = anchor_value(uy0, uy1, ay);
@@ -491,32 +440,34 @@ fn fit_with_anchor(
 }
 
 
-/// CropBox > TrimBox > MediaBox 우선으로 페이지 박스
-fn effective_page_box(doc: &Document, page_id: ObjectId) -> Option<(f64, f64, f64, f64)> {
+/// TrimBox > CropBox > MediaBox 우선으로 페이지 박스.
+/// 재단 크기(완성된 페이지)가 있으면 그것이 배치 기준이고, 블리드 영역은 제외
+pub fn effective_page_box(doc: &Document, page_id: ObjectId) -> Option<(f64, f64, f64, f64)> {
     let page = doc.get_object(page_id).ok()?.as_dict().ok()?;
     let try_box = |name: &[u8]| -> Option<(f64,f64,f64,f64)> {
         let a = page.get(name).ok()?.as_array().ok()?;
         if a.len() != 4 { return None; }
         Some((as_f64(&a[0])?, as_f64(&a[1])?, as_f64(&a[2])?, as_f64(&a[3])?))
     };
-    try_box(b"CropBox")
-        .or_else(|| try_box(b"TrimBox"))
+    try_box(b"TrimBox")
+        .or_else(|| try_box(b"CropBox"))
         .or_else(|| effective_mediabox(doc, page_id))
 }
 
-/// TODO: “실잉크 AABB(U)”를 계산하는 자리.
-/// 현재는 임시로 페이지 박스 반환. 이후 실제 U 계산기를 붙이면 그대로 품질↑
+/// “실잉크 AABB(U)”: 콘텐츠 스트림을 해석해 실제로 칠해지는 영역 (ink 모듈)
+/// 그려진 것이 없으면 None → 호출 측에서 페이지 박스로 대체
 fn page_ink_bbox(doc: &Document, page_id: ObjectId) -> Option<(f64, f64, f64, f64)> {
-    effective_page_box(doc, page_id)
+    ink::page_ink_bbox(doc, page_id)
 }
 
-pub fn apply_inner_margin(doc: &mut Document, book: Book) -> Result<(), Box<dyn Error>> {
-    // 1) Safe area (in → pt)
+pub fn apply_inner_margin(doc: &mut Document, book: &Book, bands: &Bands, policies: &FitPolicies) -> Result<(), Box<dyn Error>> {
+    // 1) Safe area (in → pt), 머리말/쪽번호/꼬리말 띠를 뺀 그림 영역
     let mut safe_left  = book.get_safe_area(true);
     let mut safe_right = book.get_safe_area(false);
     for s in [&mut safe_left, &mut safe_right] {
         s.x     *= 72.0; s.y      *= 72.0;
         s.width *= 72.0; s.height *= 72.0;
+        *s = bands.art_area(*s);
     }
     let epsilon = 0.001; // 경계 접촉 방지 미세 여유
 
@@ -524,13 +475,19 @@ pub fn apply_inner_margin(doc: &mut Document, book: Book) -> Result<(), Box<dyn
     let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
 
     for (i, pid) in page_ids.iter().enumerate() {
+        // 풀블리드 / 여백 적용 제외 페이지는 그대로 둔다
+        let meta = page_meta::get(doc, *pid);
+        if !meta.inner_margin || meta.full_bleed {
+            continue;
+        }
+
         // 2-1) 페이지/세이프 박스
         let (pb_llx, pb_lly, pb_urx, pb_ury) =
             effective_page_box(doc, *pid).ok_or("Page has no box")?;
-        let safe = if (i + 1) % 2 == 1 { // 1-based 홀수=오른쪽
-            &safe_right
-        } else {
+        let safe = if book.is_left_page(i + 1) { // 1-based, 제본 방향(LTR/RTL) 반영
             &safe_left
+        } else {
+            &safe_right
         };
         // S 박스 좌표 (epsilon으로 살짝 안쪽으로)
         let (sx0, sy0, sx1, sy1) = (
@@ -540,31 +497,36 @@ pub fn apply_inner_margin(doc: &mut Document, book: Book) -> Result<(), Box<dyn
             safe.y + safe.height - epsilon,
         );
 
-        // 2-2) U(콘텐츠 AABB) — 현재는 페이지 박스로 대체
-        let (ux0, uy0, ux1, uy1) =
-            page_ink_bbox(doc, *pid).unwrap_or((pb_llx, pb_lly, pb_urx, pb_ury));
+        // 2-2) U(콘텐츠 AABB) — 실잉크 기준, 비어 있으면 페이지 박스
+        //       폭이나 높이가 0에 가까운 잉크(가는 선 하나 등)는 스케일을 정할 수 없으므로 페이지 박스로
+        let (ux0, uy0, ux1, uy1) = page_ink_bbox(doc, *pid)
+            .filter(|u| !is_degenerate(*u))
+            .unwrap_or((pb_llx, pb_lly, pb_urx, pb_ury));
 
         // 2-3) 희소 판정 (U가 정말로 작을 때만 희소로)
         let u_area = (ux1 - ux0).max(0.0) * (uy1 - uy0).max(0.0);
         let s_area = (sx1 - sx0).max(0.0) * (sy1 - sy0).max(0.0);
         let area_ratio = if s_area > 0.0 { u_area / s_area } else { 1.0 };
 
-        // 히스테리시스/정교화 가능. 임시 기준: 0.12 미만이면 희소 취급
-        let is_sparse = area_ratio < 0.12;
+        // 섹션 정책 기준 (기본: 0.12 미만이면 희소 취급)
+        let policy = policies.for_section(&meta.section);
+        let is_sparse = area_ratio < policy.sparse_threshold;
 
         // 2-4) 피팅 모드/피벗/스케일 상한 결정
-        let (ax, ay, s_max, mode) = if is_sparse {
-            // 바닥 중앙(anchor: Center×Bottom), 업스케일 방지
-            (AxisAnchor::Center, AxisAnchor::Start, 1.0_f64, FitMode::Contain)
+        let (anchor, s_max) = if is_sparse {
+            (policy.sparse_anchor, policy.sparse_max_scale)
         } else {
-            // 일반은 중앙(anchor: Center×Center), 제한 없음(다운스케일은 자연스럽게 됨)
-            (AxisAnchor::Center, AxisAnchor::Center, f64::INFINITY, FitMode::Contain)
+            (policy.dense_anchor, policy.dense_max_scale)
         };
+        let (mut ax, mut ay, mut mode) = (anchor.x, anchor.y, policy.mode);
+        // 페이지별 오버라이드(manifest / sidecar) 우선
+        if let Some(a) = meta.anchor { (ax, ay) = (a.x, a.y); }
+        if let Some(m) = meta.fit { mode = m; }
 
         // 2-5) 변환행렬 파라미터 계산
         let (s, tx, ty) = fit_with_anchor(
-            ux0, uy0, ux1, uy1,
-            sx0, sy0, sx1, sy1,
+            (ux0, uy0, ux1, uy1),
+            (sx0, sy0, sx1, sy1),
             ax, ay, mode, s_max,
         );
 
@@ -593,10 +555,10 @@ pub fn apply_inner_margin(doc: &mut Document, book: Book) -> Result<(), Box<dyn
 
         // 페이지의 /Resources를 폼으로 이관(없으면 비움)
         let page_ro = doc.get_object(*pid)?.as_dict()?.clone();
-        if let Some(obj) = page_ro.get(b"Resources").ok() {
-            if let Some(res) = obj_as_dict_owned(obj, doc) {
-                form_dict.set("Resources", Object::Dictionary(res));
-            }
+        if let Ok(obj) = page_ro.get(b"Resources")
+            && let Some(res) = obj_as_dict_owned(obj, doc)
+        {
+            form_dict.set("Resources", Object::Dictionary(res));
         }
         // Form 객체 생성
         let form_id = {
@@ -626,6 +588,9 @@ pub fn apply_inner_margin(doc: &mut Document, book: Book) -> Result<(), Box<dyn
             pd.set("Contents", Object::Reference(draw_id));
         } // <- 여기서 가변 대여가 즉시 해제됨
 
+        // 링크 등 주석 영역도 같은 변환으로 이동
+        links::transform_annotations(doc, *pid, s, tx, ty)?;
+
     }
 
     // (선택) 쓸모없어진 객체 정리
@@ -635,18 +600,145 @@ pub fn apply_inner_margin(doc: &mut Document, book: Book) -> Result<(), Box<dyn
     Ok(())
 }
 
+/// 인쇄용 페이지 박스 (KDP 규칙):
+///  - TrimBox: 판형
+///  - MediaBox/CropBox/BleedBox: 판형 + 블리드(책등 쪽 제외). 풀블리드 페이지가 하나라도 있으면
+///    모든 페이지가 같은 크기가 되도록 전 페이지에 적용하고, 없으면 판형과 같음
+///  - ArtBox: 세이프 영역에 맞춘 페이지는 세이프 영역, 풀블리드는 BleedBox, 나머지는 판형
+///
+/// `book`은 인치 기준이며 블리드는 그림 배치에 쓴 값(`Book::get_bleed_box`)
+pub fn set_print_boxes(doc: &mut Document, book: &Book) -> Result<(), Box<dyn Error>> {
+    // 인치 → pt
+    let pt = |r: crate::binding_params::Rect| -> Object {
+        Object::Array(vec![
+            (r.x * 72.0).into(), (r.y * 72.0).into(),
+            ((r.x + r.width) * 72.0).into(), ((r.y + r.height) * 72.0).into(),
+        ])
+    };
+    let trim = pt(book.get_trim_box());
+    let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    for (i, pid) in page_ids.iter().enumerate() {
+        let meta = page_meta::get(doc, *pid);
+        let is_left = book.is_left_page(i + 1);
+        let bleed = pt(book.get_bleed_box(is_left));
+        let art = if meta.full_bleed {
+            bleed.clone()
+        } else if meta.inner_margin {
+            pt(book.get_safe_area(is_left))
+        } else {
+            trim.clone()
+        };
+        let pd = doc.get_object_mut(*pid)?.as_dict_mut()?;
+        pd.set("MediaBox", bleed.clone());
+        pd.set("CropBox", bleed.clone());
+        pd.set("BleedBox", bleed);
+        pd.set("TrimBox", trim.clone());
+        pd.set("ArtBox", art);
+    }
+    Ok(())
+}
+
+/// 판형과 크기가 다른 페이지 (1-based 위치, 원래 크기 pt)
+#[derive(Debug, Clone)]
+pub struct MismatchedPage {
+    pub index: usize,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// 판형과 같다고 볼 크기 차이 (pt)
+const SIZE_EPS: f64 = 0.5;
+
+/// front/back matter 페이지를 판형(`w_pt` x `h_pt`)에 맞춤.
+/// 크기가 다른 페이지는 콘텐츠를 Form XObject로 감싸 `mode`대로 스케일/가운데 정렬하고
+/// (crop이면 그대로 둠) 목록을 돌려준다. 페이지 박스 자체는 호출 측 `enforce_page_size`가 설정
+pub fn fit_pages_to_size(doc: &mut Document, w_pt: f64, h_pt: f64, mode: MatterResize) -> Result<Vec<MismatchedPage>, Box<dyn Error>> {
+    let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    let mut mismatched = Vec::new();
+
+    for (i, pid) in page_ids.iter().enumerate() {
+        let (llx, lly, urx, ury) = effective_page_box(doc, *pid).ok_or("Page has no box")?;
+        let (pw, ph) = (urx - llx, ury - lly);
+        let same_size = (pw - w_pt).abs() <= SIZE_EPS && (ph - h_pt).abs() <= SIZE_EPS;
+        if same_size && llx.abs() <= SIZE_EPS && lly.abs() <= SIZE_EPS {
+            continue;
+        }
+        if !same_size {
+            mismatched.push(MismatchedPage { index: i + 1, width: pw, height: ph });
+        }
+
+        let trim = (0.0, 0.0, w_pt, h_pt);
+        let center = AxisAnchor::Center;
+        let (s, tx, ty) = match mode {
+            // 크기는 맞는데 재단 박스가 원점에 있지 않으면 (블리드 포함 PDF) 옮기기만
+            _ if same_size => (1.0, -llx, -lly),
+            MatterResize::Crop => continue,
+            MatterResize::Fit => fit_with_anchor((llx, lly, urx, ury), trim, center, center, FitMode::Contain, f64::INFINITY),
+            MatterResize::Fill => fit_with_anchor((llx, lly, urx, ury), trim, center, center, FitMode::Cover, f64::INFINITY),
+            MatterResize::Center => (1.0, (w_pt - pw) / 2.0 - llx, (h_pt - ph) / 2.0 - lly),
+        };
 
-pub fn post_process_arc(doc: &mut Document) -> Result<(), Box<dyn Error>> {
-    let _ = doc.decompress();
-    remove_blank_pages(doc)?;
+        // 기존 콘텐츠(압축 해제 후 결합)를 원래 페이지 박스 크기의 폼으로
+        let content = doc.get_page_content(*pid)?;
+        let mut form_dict = Dictionary::new();
+        form_dict.set("Type", "XObject");
+        form_dict.set("Subtype", "Form");
+        form_dict.set("FormType", 1);
+        form_dict.set("BBox", Object::Array(vec![llx.into(), lly.into(), urx.into(), ury.into()]));
+        if let Some(res) = effective_resources(doc, *pid) {
+            form_dict.set("Resources", Object::Dictionary(res));
+        }
+        let form_id = doc.add_object(Stream::new(form_dict, content));
+
+        let mut xobjs = Dictionary::new();
+        xobjs.set("MATTER", Object::Reference(form_id));
+        let mut new_res = Dictionary::new();
+        new_res.set("XObject", Object::Dictionary(xobjs));
+        let draw = format!("q\n{s:.9} 0 0 {s:.9} {tx:.9} {ty:.9} cm\n/MATTER Do\nQ\n");
+        let draw_id = doc.add_object(Stream::new(Dictionary::new(), draw.into_bytes()));
+        {
+            let pd = doc.get_object_mut(*pid)?.as_dict_mut()?;
+            pd.set("Resources", Object::Dictionary(new_res));
+            pd.set("Contents", Object::Reference(draw_id));
+        }
+        links::transform_annotations(doc, *pid, s, tx, ty)?;
+    }
+    Ok(mismatched)
+}
+
+/// 제거한(`blank_dry_run`이면 제거했을) 빈 페이지 보고서를 돌려줌
+pub fn post_process_arc(
+    doc: &mut Document,
+    project: &ProjectConfig,
+    blank_dry_run: bool,
+) -> Result<Vec<RemovedPage>, Box<dyn Error>> {
+    doc.decompress();
+    let removed = remove_blank_pages(doc, project.blanks.image_tolerance(), blank_dry_run)?;
+    links::prune_dangling(doc)?;
+    // 빈 페이지 제거 후 남은 페이지 기준으로 라벨 작성
+    stamp::write_page_labels(doc, project)?;
     stamp_watermarks(doc)?;
-    _ = doc.compress();
-    Ok(())
+    doc.compress();
+    Ok(removed)
 }
 
-pub fn post_process_book(doc: &mut Document, book: Book) -> Result<(), Box<dyn Error>> {
-    let _ = doc.decompress();
-    apply_inner_margin(doc, book)?;
-    _ = doc.compress();
+pub fn post_process_book(
+    doc: &mut Document,
+    book: Book,
+    policies: &FitPolicies,
+    project: &ProjectConfig,
+) -> Result<(), Box<dyn Error>> {
+    doc.decompress();
+    let bands = stamp::Bands::new(project, &book)?;
+    apply_inner_margin(doc, &book, &bands, policies)?;
+    if let Some(cfg) = &project.folio {
+        stamp::stamp_folios(doc, &book, cfg, &project.sections)?;
+    }
+    if let Some(cfg) = &project.running {
+        stamp::stamp_running_heads(doc, &book, &bands, cfg, project)?;
+    }
+    stamp::write_page_labels(doc, project)?;
+    set_print_boxes(doc, &book)?;
+    doc.compress();
     Ok(())
 }
\ No newline at end of file
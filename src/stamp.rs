@@ -0,0 +1,534 @@
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use crate::binding_params::{Book, Rect};
+use crate::config::{FolioConfig, FolioPosition, ProjectConfig, RunningConfig, SectionConfig};
+use crate::outline;
+use crate::page_meta::{self, PageKind};
+use crate::process_pages::{effective_page_box, effective_resources, helvetica_text_width, obj_as_dict_owned};
+
+/// 쪽번호 표기 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberStyle {
+    /// i, ii, iii …
+    Roman,
+    /// 1, 2, 3 …
+    Arabic,
+}
+
+/// 한 페이지의 쪽번호 (표기 방식 + 값 + 섹션 접두어)
+#[derive(Debug, Clone)]
+pub struct PageNumber {
+    pub style: NumberStyle,
+    pub value: i64,
+    pub prefix: String,
+}
+
+impl PageNumber {
+    pub fn label(&self) -> String {
+        match self.style {
+            NumberStyle::Roman => format!("{}{}", self.prefix, to_roman(self.value)),
+            NumberStyle::Arabic => format!("{}{}", self.prefix, self.value),
+        }
+    }
+}
+
+/// 소문자 로마 숫자 (1 미만은 아라비아 숫자로)
+pub fn to_roman(mut n: i64) -> String {
+    if n < 1 {
+        return n.to_string();
+    }
+    const TABLE: [(i64, &str); 13] = [
+        (1000, "m"), (900, "cm"), (500, "d"), (400, "cd"),
+        (100, "c"), (90, "xc"), (50, "l"), (40, "xl"),
+        (10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i"),
+    ];
+    let mut out = String::new();
+    for (v, s) in TABLE {
+        while n >= v {
+            out.push_str(s);
+            n -= v;
+        }
+    }
+    out
+}
+
+/// 모든 페이지의 쪽번호 (표시 여부와 무관하게 빈 페이지/풀블리드도 번호를 소비)
+///  - front_roman: front 섹션(그 안의 빈 페이지 포함)은 i부터, 나머지는 `start`부터 다시
+///  - 아니면 첫 페이지부터 `start`로 시작하는 하나의 아라비아 숫자 열
+///  - `[sections.<name>]`의 `first_number`가 있으면 그 섹션 첫 페이지에서 다시 시작, `label_prefix`는 앞에 붙임
+pub fn page_numbers(doc: &Document, cfg: &FolioConfig, sections: &HashMap<String, SectionConfig>) -> Vec<PageNumber> {
+    let mut front = 0;
+    let mut body = cfg.start;
+    let mut prev_section: Option<String> = None;
+    doc.get_pages().values()
+        .map(|pid| {
+            let meta = page_meta::get(doc, *pid);
+            let section = sections.get(&meta.section);
+            if prev_section.as_deref() != Some(meta.section.as_str()) {
+                if let Some(n) = section.and_then(|s| s.first_number) {
+                    body = n;
+                }
+                prev_section = Some(meta.section.clone());
+            }
+            let prefix = section.and_then(|s| s.label_prefix.clone()).unwrap_or_default();
+            if cfg.front_roman && meta.is_front() {
+                front += 1;
+                PageNumber { style: NumberStyle::Roman, value: front, prefix }
+            } else {
+                body += 1;
+                PageNumber { style: NumberStyle::Arabic, value: body - 1, prefix }
+            }
+        })
+        .collect()
+}
+
+/// 카탈로그에 `/PageLabels` 숫자 트리 기록 (쪽번호 체계와 동일).
+/// 표기 방식/접두어가 바뀌거나 번호가 이어지지 않는 곳마다 새 구간을 연다.
+/// 페이지 삭제(ARC의 빈 페이지 제거 등) 이후에 호출해야 인덱스가 맞는다
+pub fn write_page_labels(doc: &mut Document, project: &ProjectConfig) -> Result<(), Box<dyn Error>> {
+    let numbers = page_numbers(doc, &project.folio.clone().unwrap_or_default(), &project.sections);
+    let mut nums = Vec::new();
+    let mut prev: Option<&PageNumber> = None;
+    for (i, n) in numbers.iter().enumerate() {
+        let continues = prev.is_some_and(|p| {
+            p.style == n.style && p.prefix == n.prefix && p.value + 1 == n.value
+        });
+        if !continues {
+            let mut range = Dictionary::new();
+            range.set("S", match n.style {
+                NumberStyle::Roman => "r",
+                NumberStyle::Arabic => "D",
+            });
+            if n.value != 1 {
+                range.set("St", n.value);
+            }
+            if !n.prefix.is_empty() {
+                range.set("P", outline::text_string(&n.prefix));
+            }
+            nums.push(Object::Integer(i as i64));
+            nums.push(Object::Dictionary(range));
+        }
+        prev = Some(n);
+    }
+
+    let labels_id = doc.add_object(dictionary! { "Nums" => nums });
+    doc.catalog_mut()?.set("PageLabels", Object::Reference(labels_id));
+    Ok(())
+}
+
+/// 페이지별 설정과 `[folio]` 제외 규칙을 함께 적용
+fn shows_folio(meta: &page_meta::PageMeta, cfg: &FolioConfig) -> bool {
+    meta.folio
+        && !(cfg.skip_front && meta.is_front())
+        && !(cfg.skip_blank && meta.kind == PageKind::Blank)
+        && !(cfg.skip_full_bleed && meta.full_bleed)
+}
+
+/// 표준 14 폰트 기준 문자열 폭 (Courier는 고정폭, 나머지는 Helvetica 폭으로 근사)
+fn text_width(font: &str, s: &str, size: f64) -> f64 {
+    if font.starts_with("Courier") {
+        s.chars().count() as f64 * 0.6 * size
+    } else {
+        helvetica_text_width(s, size)
+    }
+}
+
+/// 표준 14 폰트의 대략적인 어센트/디센트 (글자 크기 대비). 글줄 상자 = 기준선 아래 DESCENT ~ 위 ASCENT
+const ASCENT: f64 = 0.75;
+const DESCENT: f64 = 0.25;
+
+/// 유니코드 문자 → WinAnsiEncoding 코드 (표현할 수 없으면 None)
+fn win_ansi_code(c: char) -> Option<u8> {
+    if matches!(c as u32, 0x20..=0x7E | 0xA0..=0xFF) {
+        return Some(c as u32 as u8);
+    }
+    // 0x80..=0x9F 구간 (PDF 32000 부록 D.2)
+    Some(match c {
+        '€' => 0x80, '‚' => 0x82, 'ƒ' => 0x83, '„' => 0x84, '…' => 0x85, '†' => 0x86,
+        '‡' => 0x87, 'ˆ' => 0x88, '‰' => 0x89, 'Š' => 0x8A, '‹' => 0x8B, 'Œ' => 0x8C,
+        'Ž' => 0x8E, '‘' => 0x91, '’' => 0x92, '“' => 0x93, '”' => 0x94, '•' => 0x95,
+        '–' => 0x96, '—' => 0x97, '˜' => 0x98, '™' => 0x99, 'š' => 0x9A, '›' => 0x9B,
+        'œ' => 0x9C, 'ž' => 0x9E, 'Ÿ' => 0x9F,
+        _ => return None,
+    })
+}
+
+/// WinAnsi로 옮길 수 없는 문자는 `?`로 바꾸고 `missing`에 모음 (표준 14 폰트는 임베드하지 않으므로)
+pub fn to_win_ansi(s: &str, missing: &mut BTreeSet<char>) -> String {
+    s.chars()
+        .map(|c| {
+            if win_ansi_code(c).is_some() {
+                c
+            } else {
+                missing.insert(c);
+                '?'
+            }
+        })
+        .collect()
+}
+
+/// 바꾼 문자가 있으면 한 번만 경고
+pub fn warn_missing(what: &str, missing: &BTreeSet<char>) {
+    if !missing.is_empty() {
+        let chars: String = missing.iter().collect();
+        eprintln!("Warning: {what}: characters not in WinAnsiEncoding were replaced with '?': {chars}");
+    }
+}
+
+/// PDF 리터럴 문자열 본문 (WinAnsi 코드, 출력 가능한 ASCII 밖은 8진 이스케이프)
+pub fn escape_pdf_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match win_ansi_code(c).unwrap_or(b'?') {
+            code @ (b'(' | b')' | b'\\') => {
+                out.push('\\');
+                out.push(code as char);
+            }
+            code @ 0x20..=0x7E => out.push(code as char),
+            code => out.push_str(&format!("\\{code:03o}")),
+        }
+    }
+    out
+}
+
+/// 세이프 영역 위/아래에서 머리말, 쪽번호/꼬리말용으로 떼어 두는 띠 높이 (pt).
+/// `apply_inner_margin`은 이 띠를 뺀 나머지에 그림을 맞추고, 글자는 띠 안에만 찍는다.
+/// 아래 띠는 바닥부터 쪽번호 글줄, 그 위에 꼬리말 글줄 순으로 쌓인다
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bands {
+    pub header: f64,
+    pub footer: f64,
+    /// 아래 띠 중 쪽번호 몫 (꼬리말은 이만큼 위에서 시작)
+    pub folio: f64,
+}
+
+impl Bands {
+    /// 띠가 세이프 영역을 다 차지하면 그림 놓을 자리가 없으므로 설정 오류
+    pub fn new(project: &ProjectConfig, book: &Book) -> Result<Bands, Box<dyn Error>> {
+        let mut bands = Bands::default();
+        if let Some(cfg) = &project.folio {
+            bands.folio = cfg.size * (ASCENT + DESCENT) + cfg.offset;
+            bands.footer = bands.folio;
+        }
+        if let Some(cfg) = &project.running {
+            let band = cfg.size * (ASCENT + DESCENT) + cfg.offset;
+            let (header, footer) = running_slots(cfg, project);
+            if header {
+                bands.header = band;
+            }
+            if footer {
+                bands.footer += band;
+            }
+        }
+        let safe_h = book.get_safe_area_size().height * 72.0;
+        if bands.header + bands.footer >= safe_h {
+            return Err(format!(
+                "[folio]/[running] size and offset need {:.1}pt of bands but the safe area is only {safe_h:.1}pt tall",
+                bands.header + bands.footer,
+            ).into());
+        }
+        Ok(bands)
+    }
+
+    /// 그림이 들어갈 영역: 세이프 영역(pt)에서 띠를 뺀 나머지
+    pub fn art_area(&self, safe: Rect) -> Rect {
+        Rect { y: safe.y + self.footer, height: safe.height - self.header - self.footer, ..safe }
+    }
+}
+
+/// 전역 또는 섹션 템플릿 중 머리말/꼬리말이 하나라도 찍힐 수 있는지 (빈 문자열은 끔)
+fn running_slots(cfg: &RunningConfig, project: &ProjectConfig) -> (bool, bool) {
+    let set = |t: &Option<String>| t.as_deref().is_some_and(|t| !t.trim().is_empty());
+    std::iter::once(None)
+        .chain(project.sections.values().map(Some))
+        .map(|section| cfg.templates(section))
+        .fold((false, false), |(header, footer), t| {
+            (
+                header || set(&t.header_verso) || set(&t.header_recto),
+                footer || set(&t.footer_verso) || set(&t.footer_recto),
+            )
+        })
+}
+
+/// 쪽번호 찍기. 좌/우(verso/recto) 판정과 세이프 영역은 `apply_inner_margin`과 동일.
+/// 쪽번호 글줄은 세이프 영역 아래쪽 띠(`Bands`)의 바닥에 놓여 그림과 겹치지 않는다
+pub fn stamp_folios(
+    doc: &mut Document,
+    book: &Book,
+    cfg: &FolioConfig,
+    sections: &HashMap<String, SectionConfig>,
+) -> Result<(), Box<dyn Error>> {
+    let font_id = {
+        let mut d = Dictionary::new();
+        d.set("Type", "Font");
+        d.set("Subtype", "Type1");
+        d.set("BaseFont", Object::Name(cfg.font.as_bytes().to_vec()));
+        d.set("Encoding", "WinAnsiEncoding");
+        let id = doc.new_object_id();
+        doc.objects.insert(id, Object::Dictionary(d));
+        id
+    };
+
+    let numbers = page_numbers(doc, cfg, sections);
+    let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    let mut missing = BTreeSet::new();
+    for (i, pid) in page_ids.iter().enumerate() {
+        let meta = page_meta::get(doc, *pid);
+        if !shows_folio(&meta, cfg) {
+            continue;
+        }
+
+        let is_left = book.is_left_page(i + 1);
+        let mut safe = book.get_safe_area(is_left);
+        safe.x *= 72.0; safe.y *= 72.0;
+        safe.width *= 72.0; safe.height *= 72.0;
+        // 페이지 박스 원점 보정
+        let (llx, lly, _, _) = effective_page_box(doc, *pid).ok_or("Page has no box")?;
+
+        let label = to_win_ansi(&numbers[i].label(), &mut missing);
+        let tw = text_width(&cfg.font, &label, cfg.size);
+        let x = match cfg.position {
+            FolioPosition::OuterCorner if is_left => safe.x,
+            FolioPosition::OuterCorner => safe.x + safe.width - tw,
+            FolioPosition::BottomCenter => safe.x + (safe.width - tw) / 2.0,
+        };
+        // 글줄 상자 아랫변이 세이프 영역 아래 가장자리에 닿도록 (그림과는 offset만큼 떨어짐)
+        let y = safe.y + DESCENT * cfg.size;
+
+        let content = format!(
+            "BT\n/F_FOLIO {size:.3} Tf\n{x:.3} {y:.3} Td\n({text}) Tj\nET\n",
+            size = cfg.size, x = llx + x, y = lly + y, text = escape_pdf_text(&label),
+        );
+        overlay(doc, *pid, content, &[("F_FOLIO", font_id)])?;
+    }
+    warn_missing("folio labels", &missing);
+    Ok(())
+}
+
+/// `{title}`, `{section}`, `{page}`, `{total}` 치환
+fn fill_template(template: &str, title: &str, section: &str, page: &str, total: usize) -> String {
+    template
+        .replace("{title}", title)
+        .replace("{section}", section)
+        .replace("{page}", page)
+        .replace("{total}", &total.to_string())
+}
+
+/// 머리말/꼬리말 찍기. 그림과 겹치지 않도록 세이프 영역 위/아래 띠(`Bands`) 안에 가운데 정렬.
+/// 풀블리드/빈 페이지는 제외, `{page}`는 `[folio]` 번호 체계를 따른다
+pub fn stamp_running_heads(
+    doc: &mut Document,
+    book: &Book,
+    bands: &Bands,
+    cfg: &RunningConfig,
+    project: &ProjectConfig,
+) -> Result<(), Box<dyn Error>> {
+    let font_id = {
+        let mut d = Dictionary::new();
+        d.set("Type", "Font");
+        d.set("Subtype", "Type1");
+        d.set("BaseFont", Object::Name(cfg.font.as_bytes().to_vec()));
+        d.set("Encoding", "WinAnsiEncoding");
+        let id = doc.new_object_id();
+        doc.objects.insert(id, Object::Dictionary(d));
+        id
+    };
+
+    let numbers = page_numbers(doc, &project.folio.clone().unwrap_or_default(), &project.sections);
+    let title = project.title.as_deref().unwrap_or_default();
+    let page_ids: Vec<ObjectId> = doc.get_pages().values().cloned().collect();
+    let total = page_ids.len();
+    let mut missing = BTreeSet::new();
+    for (i, pid) in page_ids.iter().enumerate() {
+        let meta = page_meta::get(doc, *pid);
+        if meta.full_bleed
+            || meta.kind == PageKind::Blank
+            || (cfg.skip_front && meta.is_front())
+        {
+            continue;
+        }
+
+        let is_left = book.is_left_page(i + 1);
+        let mut safe = book.get_safe_area(is_left);
+        safe.x *= 72.0; safe.y *= 72.0;
+        safe.width *= 72.0; safe.height *= 72.0;
+        let (llx, lly, _, _) = effective_page_box(doc, *pid).ok_or("Page has no box")?;
+
+        let templates = cfg.templates(project.sections.get(&meta.section));
+        let (header, footer) = if is_left {
+            (templates.header_verso, templates.footer_verso)
+        } else {
+            (templates.header_recto, templates.footer_recto)
+        };
+        let section = project.section_title(&meta.section);
+        let page = numbers[i].label();
+
+        let mut content = String::new();
+        // 머리말 글줄 윗변은 세이프 영역 위 가장자리에, 꼬리말 글줄 아랫변은 쪽번호 몫 바로 위에
+        for (template, y) in [
+            (header, safe.y + safe.height - ASCENT * cfg.size),
+            (footer, safe.y + bands.folio + DESCENT * cfg.size),
+        ] {
+            let Some(template) = template else { continue };
+            let text = to_win_ansi(&fill_template(&template, title, section, &page, total), &mut missing);
+            if text.trim().is_empty() {
+                continue;
+            }
+            let x = safe.x + (safe.width - text_width(&cfg.font, &text, cfg.size)) / 2.0;
+            content.push_str(&format!(
+                "BT\n/F_RUN {size:.3} Tf\n{x:.3} {y:.3} Td\n({text}) Tj\nET\n",
+                size = cfg.size, x = llx + x, y = lly + y, text = escape_pdf_text(&text),
+            ));
+        }
+        if !content.is_empty() {
+            overlay(doc, *pid, content, &[("F_RUN", font_id)])?;
+        }
+    }
+    warn_missing("running heads", &missing);
+    Ok(())
+}
+
+/// 기존 페이지 내용 위에 `content`를 덧그린다.
+/// 기존 Contents를 q … Q로 감싸 그래픽 상태가 새지 않게 하고, `fonts`를 페이지 리소스에 등록
+pub fn overlay(
+    doc: &mut Document,
+    page_id: ObjectId,
+    content: String,
+    fonts: &[(&str, ObjectId)],
+) -> Result<(), Box<dyn Error>> {
+    // 1) 리소스 (상속된 것까지 페이지에 직접 복사)
+    let mut resources = effective_resources(doc, page_id).unwrap_or_default();
+    let mut fr = match resources.get(b"Font") {
+        Ok(o) => obj_as_dict_owned(o, doc).unwrap_or_default(),
+        Err(_) => Dictionary::new(),
+    };
+    for (name, id) in fonts {
+        fr.set(*name, Object::Reference(*id));
+    }
+    resources.set("Font", Object::Dictionary(fr));
+
+    // 2) Contents: [q] + 기존 + [Q + overlay]
+    let old: Vec<Object> = match doc.get_object(page_id)?.as_dict()?.get(b"Contents") {
+        Ok(Object::Array(a)) => a.clone(),
+        Ok(o @ Object::Reference(_)) => vec![o.clone()],
+        _ => Vec::new(),
+    };
+    let mut contents = Vec::with_capacity(old.len() + 2);
+    if !old.is_empty() {
+        let open_id = doc.add_object(Stream::new(Dictionary::new(), b"q\n".to_vec()));
+        contents.push(Object::Reference(open_id));
+        contents.extend(old);
+    }
+    // 스트림 경계에서 토큰이 붙지 않도록 개행으로 시작
+    let body = if contents.is_empty() { content } else { format!("\nQ\n{content}") };
+    let overlay_id = doc.add_object(Stream::new(Dictionary::new(), body.into_bytes()));
+    contents.push(Object::Reference(overlay_id));
+
+    let pd = doc.get_object_mut(page_id)?.as_dict_mut()?;
+    pd.set("Resources", Object::Dictionary(resources));
+    pd.set("Contents", Object::Array(contents));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SectionConfig;
+    use crate::page_meta::PageMeta;
+    use crate::page_tree;
+
+    /// 섹션 이름 목록대로 페이지를 만든 문서 (front 섹션은 PageKind::Front)
+    fn doc_with_sections(sections: &[&str]) -> Document {
+        let mut doc = Document::with_version("1.7");
+        let root = doc.new_object_id();
+        let pages: Vec<ObjectId> = sections.iter()
+            .map(|_| doc.add_object(dictionary! { "Type" => "Page" }))
+            .collect();
+        page_tree::build_balanced(&mut doc, root, &pages).unwrap();
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => root });
+        doc.trailer.set("Root", catalog);
+        for (pid, section) in pages.iter().zip(sections) {
+            let kind = if *section == "front" { PageKind::Front } else { PageKind::Art };
+            let mut meta = PageMeta::new(kind);
+            meta.section = section.to_string();
+            page_meta::set(&mut doc, *pid, &meta).unwrap();
+        }
+        doc
+    }
+
+    fn labels(doc: &Document) -> Vec<Object> {
+        let catalog = doc.catalog().unwrap();
+        let id = catalog.get(b"PageLabels").unwrap().as_reference().unwrap();
+        doc.get_object(id).unwrap().as_dict().unwrap().get(b"Nums").unwrap().as_array().unwrap().clone()
+    }
+
+    fn range(style: &str, start: Option<i64>, prefix: Option<Object>) -> Object {
+        let mut d = dictionary! { "S" => style };
+        if let Some(st) = start {
+            d.set("St", st);
+        }
+        if let Some(p) = prefix {
+            d.set("P", p);
+        }
+        Object::Dictionary(d)
+    }
+
+    #[test]
+    fn roman_numerals() {
+        let cases = [(1, "i"), (4, "iv"), (9, "ix"), (14, "xiv"), (40, "xl"), (90, "xc"), (400, "cd"), (1994, "mcmxciv")];
+        for (n, roman) in cases {
+            assert_eq!(to_roman(n), roman);
+        }
+        assert_eq!(to_roman(0), "0");
+    }
+
+    #[test]
+    fn front_is_roman_then_body_restarts() {
+        let doc = doc_with_sections(&["front", "front", "body", "body", "body"]);
+        let numbers: Vec<String> = page_numbers(&doc, &FolioConfig::default(), &HashMap::new())
+            .iter().map(PageNumber::label).collect();
+        assert_eq!(numbers, ["i", "ii", "1", "2", "3"]);
+
+        let cfg = FolioConfig { front_roman: false, start: 5, ..FolioConfig::default() };
+        let numbers: Vec<String> = page_numbers(&doc, &cfg, &HashMap::new())
+            .iter().map(PageNumber::label).collect();
+        assert_eq!(numbers, ["5", "6", "7", "8", "9"]);
+    }
+
+    #[test]
+    fn page_label_ranges_follow_numbering() {
+        let mut doc = doc_with_sections(&["front", "front", "body", "body", "appendix", "appendix"]);
+        let mut project = ProjectConfig::default();
+        project.sections.insert(String::from("appendix"), SectionConfig {
+            first_number: Some(1),
+            label_prefix: Some(String::from("A-")),
+            ..SectionConfig::default()
+        });
+        write_page_labels(&mut doc, &project).unwrap();
+        assert_eq!(labels(&doc), vec![
+            0.into(), range("r", None, None),
+            2.into(), range("D", None, None),
+            4.into(), range("D", None, Some(Object::string_literal("A-"))),
+        ]);
+    }
+
+    #[test]
+    fn page_labels_record_start_and_unicode_prefix() {
+        let mut doc = doc_with_sections(&["body", "body", "notes"]);
+        let mut project = ProjectConfig {
+            folio: Some(FolioConfig { start: 7, ..FolioConfig::default() }),
+            ..ProjectConfig::default()
+        };
+        project.sections.insert(String::from("notes"), SectionConfig {
+            label_prefix: Some(String::from("Ω")),
+            ..SectionConfig::default()
+        });
+        write_page_labels(&mut doc, &project).unwrap();
+        // 접두어가 바뀌면 번호가 이어져도 새 구간. 비 ASCII 접두어는 UTF-16BE 텍스트 문자열
+        assert_eq!(labels(&doc), vec![
+            0.into(), range("D", Some(7), None),
+            2.into(), range("D", Some(9), Some(outline::text_string("Ω"))),
+        ]);
+    }
+}